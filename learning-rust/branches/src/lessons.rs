@@ -0,0 +1,222 @@
+// The control flow sections and exercises, one function per section.
+// `main` runs them all in order, `learn` runs, shows and checks them one by one.
+
+use lessons::{Course, Lesson};
+
+pub const COURSE: Course = Course {
+  name: "branches",
+  title: "Control Flow",
+  source: include_str!("lessons.rs"),
+  golden: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"),
+  prepare: || {},
+  lessons: &[
+    Lesson { id: "if_expressions", title: "Control Flow", run: if_expressions },
+    Lesson { id: "multiple_conditions", title: "Multiple conditions", run: multiple_conditions },
+    Lesson { id: "if_in_let", title: "if in a let Statement", run: if_in_let },
+    Lesson { id: "loops", title: "Repetition with Loops", run: loops },
+    Lesson { id: "loop_values", title: "Returning a value from loop", run: loop_values },
+    Lesson { id: "while_loops", title: "Conditional loops", run: while_loops },
+    Lesson {
+      id: "while_collection",
+      title: "Looping through a collection with while",
+      run: while_collection,
+    },
+    Lesson {
+      id: "for_collection",
+      title: "Looping through a collection with for",
+      run: for_collection,
+    },
+    Lesson {
+      id: "for_range",
+      title: "Looping through a collection with for with reverse range",
+      run: for_range,
+    },
+    Lesson { id: "temperatures", title: "Exercise: temperatures", run: temperatures },
+    Lesson { id: "fibonacci", title: "Exercise: Fibonacci numbers", run: fibonacci },
+    Lesson { id: "carol", title: "Exercise: a Christmas carol", run: carol },
+  ],
+};
+
+pub fn if_expressions() {
+  let number = 3;
+  // Rust expected a bool
+  // Unlike languages such as Ruby and JavaScript, Rust will not automatically try to convert non-Boolean types to a Boolean
+  if number < 5 {
+      println!("condition was true");
+  } else {
+      println!("condition was false");
+  }
+}
+
+pub fn multiple_conditions() {
+  // Using too many else if expressions can clutter your code, so if you have more than one, you might want to refactor your code
+  let number = 6;
+
+  if number % 4 == 0 {
+      println!("number is divisible by 4");
+  } else if number % 3 == 0 {
+      println!("number is divisible by 3");
+  } else if number % 2 == 0 {
+      println!("number is divisible by 2");
+  } else {
+      println!("number is not divisible by 4, 3, or 2");
+  }
+}
+
+pub fn if_in_let() {
+  let condition = true;
+  let number = if condition { 5 } else { 6 };
+
+  println!("The value of number is: {}", number);
+}
+
+//loop, while, and for
+pub fn loops() {
+  // infinite loop
+  // break - stop executing the loop
+  // continue - skip over any remaining code in this iteration
+  // label - 'counting_up
+  let mut count = 0;
+  'counting_up: loop {
+      println!("count = {}", count);
+      let mut remaining = 10;
+
+      loop {
+          println!("remaining = {}", remaining);
+          if remaining == 9 {
+              break;
+          }
+          if count == 2 {
+              break 'counting_up;
+          }
+          remaining -= 1;
+      }
+
+      count += 1;
+  }
+  println!("End count = {}", count);
+}
+
+pub fn loop_values() {
+  let mut counter = 0;
+
+  let result = loop {
+      counter += 1;
+
+      if counter == 10 {
+          break counter * 2;
+      }
+  };
+
+  println!("The result is {}", result);
+}
+
+pub fn while_loops() {
+  let mut number = 3;
+
+  while number != 0 {
+    println!("{}!", number);
+
+    number -= 1;
+  }
+  println!("Liftoff!!!");
+}
+
+pub fn while_collection() {
+  let a = [10, 20, 30, 40, 50];
+  let mut index = 0;
+
+  while index < 5 {
+    println!("the value is: {}", a[index]);
+
+    index += 1;
+  }
+}
+
+pub fn for_collection() {
+  let a = [10, 20, 30, 40, 50];
+
+  for element in a {
+    println!("the value is: {}", element);
+  }
+}
+
+pub fn for_range() {
+  for number in (1..4).rev() {
+    println!("the value is: {}", number);
+  }
+  println!("Liftoff!");
+}
+
+// Exercises
+// Convert temperatures between Fahrenheit and Celsius.
+pub fn temperatures() {
+  fn temp_conversion(input: f32, celcius: bool  ) -> f32  {
+
+    // input type is Celcius
+    // X°F = (input°C × 9/5) + 32
+    // X°C = (input°F − 32) × 5/9
+    if celcius {
+      input * (9.0 / 5.0) + 32.0
+    } else {
+      (input - 32.0) * (5.0 / 9.0)
+    }
+  }
+
+  let temp: f32 = temp_conversion(55.0, false);
+  println!("55 °F = {} °C ", temp);
+
+  let temp: f32 = temp_conversion(30.0, true);
+  println!("30 °C = {} °F ", temp);
+}
+
+// Generate the nth Fibonacci number
+pub fn fibonacci() {
+  fn gen_fib_number(n: i32) -> i32 {
+    if n <= 1 {
+      return n;
+    }
+    gen_fib_number(n - 1) + gen_fib_number(n - 2)
+  }
+
+  for i in 1..10 {
+    let fib = gen_fib_number(i);
+    println!("{}th number of Fibonacci is {}", i, fib);
+  }
+}
+
+// Print the lyrics to the Christmas carol “The Twelve Days of Christmas,” taking advantage of the repetition in the song.
+pub fn carol() {
+
+  println!();
+  // 4 sections
+  for s in 1..5 {
+    for l in 1..5 {
+      if ((s == 1) || (s == 2)) && (l == 1) {
+        println!("Have yourself a merry little Christmas,");
+      } else if (s == 1) && (l == 2) {
+        println!("Let your heart be light");
+      } else if ((s == 1)|(s == 2)) && (l == 3) {
+        println!("From now on,");
+      } else if (s == 2) && (l == 2) {
+        println!("Make the Yule-tide gay,");
+      } else if ((s == 1)|(s == 2)) && (l == 4) {
+        print!("Our troubles will be ");
+        if s == 1 { println!("out of sight "); }
+        else { println!("miles away."); }
+      } else if (s == 3) && (l == 1) {
+        println!("Here we are as in olden days,\n\
+          Happy golden days of yore.\n\
+          Faithful friends who are dear to us\n\
+          Gather near to us once more.");
+      } else if (s == 4) && (l == 1) {
+        println!("Through the years\n\
+        We all will be together,\n\
+        If the Fates allow\n\
+        Hang a shining star upon the highest bough.\n\
+        And have yourself A merry little Christmas now.");
+      }
+    }
+    println!();
+  }
+}
@@ -0,0 +1,287 @@
+// Animated output: the message is typed into the bubble one grapheme cluster
+// at a time, then the character blinks or waves.
+//
+// Frames are generated without touching the terminal, `Animation::frames`
+// returns them as plain values (a frame prints as its text) so they can be
+// tested. `play` is the only part that writes escape sequences.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::render::Renderer;
+use crate::style::{paint, Line, Theme};
+
+// Number of frames the character moves after the message is typed
+const MOTION_FRAMES: usize = 6;
+
+// ANSI control sequences used while playing
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const CLEAR_TO_END: &str = "\x1b[J";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motion {
+    // the character stands still
+    Still,
+    // the eyes close every other frame
+    Blink,
+    // the character sways left and right
+    Wave,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+    // how long every typed character stays on screen
+    pub typing: Duration,
+    // how long every pose of the character stays on screen
+    pub motion: Duration,
+}
+
+impl Default for Timing {
+    fn default() -> Timing {
+        Timing {
+            typing: Duration::from_millis(50),
+            motion: Duration::from_millis(300),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub lines: Vec<Line>,
+    // how long the frame is shown before the next one
+    pub delay: Duration,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&paint(&self.lines, None))
+    }
+}
+
+pub struct Animation<'a> {
+    renderer: &'a Renderer,
+    message: &'a str,
+    motion: Motion,
+    timing: Timing,
+}
+
+impl<'a> Animation<'a> {
+    pub fn new(renderer: &'a Renderer, message: &'a str) -> Animation<'a> {
+        Animation {
+            renderer,
+            message,
+            motion: Motion::Blink,
+            timing: Timing::default(),
+        }
+    }
+
+    pub fn motion(mut self, motion: Motion) -> Animation<'a> {
+        self.motion = motion;
+        self
+    }
+
+    pub fn timing(mut self, timing: Timing) -> Animation<'a> {
+        self.timing = timing;
+        self
+    }
+
+    // Every frame in order. The last one is always the still picture
+    // that `Renderer::render_lines` draws.
+    pub fn frames(&self) -> Vec<Frame> {
+        let character = self.renderer.current_character();
+        let typed: usize = self
+            .renderer
+            .text_lines(self.message)
+            .iter()
+            .map(|line| line.graphemes(true).count())
+            .sum();
+
+        let mut frames = Vec::new();
+        for shown in 0..typed {
+            frames.push(Frame {
+                lines: self
+                    .renderer
+                    .render_frame(self.message, Some(shown), character),
+                delay: self.timing.typing,
+            });
+        }
+
+        for step in 0..MOTION_FRAMES {
+            let posed = match self.motion {
+                Motion::Still => break,
+                Motion::Blink if step % 2 == 1 => character.blinking(),
+                Motion::Blink => character.clone(),
+                Motion::Wave => character.waving(step % 2),
+            };
+            frames.push(Frame {
+                lines: self.renderer.render_frame(self.message, None, &posed),
+                delay: self.timing.motion,
+            });
+        }
+
+        frames.push(Frame {
+            lines: self.renderer.render_lines(self.message),
+            delay: Duration::ZERO,
+        });
+        frames
+    }
+}
+
+// Plays the frames on a terminal, every frame is drawn over the previous one
+pub fn play<W: Write>(frames: &[Frame], writer: &mut W, theme: Option<&Theme>) -> io::Result<()> {
+    let cursor = HiddenCursor::hide(writer)?;
+    let mut previous_height = 0;
+    for frame in frames {
+        if previous_height > 0 {
+            // back to the first line of the previous frame and wipe it
+            write!(cursor.writer, "\x1b[{}A\r{}", previous_height, CLEAR_TO_END)?;
+        }
+        cursor
+            .writer
+            .write_all(paint(&frame.lines, theme).as_bytes())?;
+        cursor.writer.flush()?;
+        thread::sleep(frame.delay);
+        previous_height = frame.lines.len();
+    }
+    cursor.show()
+}
+
+// Hides the cursor until `show` is called, or until it is dropped on an
+// error or a panic, so the terminal never stays without a cursor
+struct HiddenCursor<'a, W: Write> {
+    writer: &'a mut W,
+    shown: bool,
+}
+
+impl<'a, W: Write> HiddenCursor<'a, W> {
+    fn hide(writer: &'a mut W) -> io::Result<HiddenCursor<'a, W>> {
+        write!(writer, "{}", HIDE_CURSOR)?;
+        Ok(HiddenCursor {
+            writer,
+            shown: false,
+        })
+    }
+
+    fn show(mut self) -> io::Result<()> {
+        self.shown = true;
+        write!(self.writer, "{}", SHOW_CURSOR)?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for HiddenCursor<'_, W> {
+    fn drop(&mut self) {
+        if !self.shown {
+            // nothing to do about a writer that fails again
+            let _ = write!(self.writer, "{}", SHOW_CURSOR);
+            let _ = self.writer.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(motion: Motion) -> Vec<String> {
+        let renderer = Renderer::new();
+        let animation = Animation::new(&renderer, "hi").motion(motion);
+        animation.frames().iter().map(Frame::to_string).collect()
+    }
+
+    #[test]
+    fn message_is_typed_into_a_full_size_bubble() {
+        let frames = frames(Motion::Still);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].starts_with(" ____\n<    >\n"));
+        assert!(frames[1].starts_with(" ____\n< h  >\n"));
+        assert_eq!(frames[2], Renderer::new().render("hi"));
+    }
+
+    #[test]
+    fn blinking_closes_the_eyes_every_other_frame() {
+        let frames = frames(Motion::Blink);
+        assert_eq!(frames.len(), 2 + MOTION_FRAMES + 1);
+        assert!(frames[2].contains("o o"));
+        assert!(frames[3].contains("- -"));
+        assert!(frames.last().unwrap().contains("o o"));
+    }
+
+    #[test]
+    fn waving_keeps_the_tail_in_place() {
+        let frames = frames(Motion::Wave);
+        let still = Renderer::new().render("hi");
+        let tail: Vec<&str> = still.lines().take(5).collect();
+        for frame in &frames[2..2 + MOTION_FRAMES] {
+            assert_eq!(frame.lines().take(5).collect::<Vec<_>>(), tail);
+            assert_ne!(frame, &still);
+        }
+    }
+
+    #[test]
+    fn play_moves_the_cursor_back_over_the_previous_frame() {
+        let renderer = Renderer::new();
+        let frames = Animation::new(&renderer, "hi")
+            .motion(Motion::Still)
+            .timing(Timing {
+                typing: Duration::ZERO,
+                motion: Duration::ZERO,
+            })
+            .frames();
+        let mut out = Vec::new();
+        play(&frames, &mut out, None).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(HIDE_CURSOR));
+        assert!(out.ends_with(SHOW_CURSOR));
+        assert_eq!(out.matches("\x1b[9A\r\x1b[J").count(), 2);
+    }
+
+    // Takes everything except the write with number `fail_at`, like a pipe
+    // whose reader went away for a moment
+    struct Flaky {
+        out: Vec<u8>,
+        writes: usize,
+        fail_at: usize,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes += 1;
+            if self.writes == self.fail_at {
+                return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+            }
+            self.out.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn play_shows_the_cursor_again_after_an_error() {
+        let renderer = Renderer::new();
+        let frames = Animation::new(&renderer, "hi")
+            .motion(Motion::Still)
+            .timing(Timing {
+                typing: Duration::ZERO,
+                motion: Duration::ZERO,
+            })
+            .frames();
+        let mut writer = Flaky {
+            out: Vec::new(),
+            writes: 0,
+            fail_at: 3,
+        };
+        let error = play(&frames, &mut writer, None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        let out = String::from_utf8(writer.out).unwrap();
+        assert!(out.starts_with(HIDE_CURSOR));
+        assert!(out.ends_with(SHOW_CURSOR));
+    }
+}
@@ -0,0 +1,383 @@
+// The ASCII art characters that can hold the bubble.
+//
+// A character file has a small header, a `---` line and the art itself:
+//
+//     # comments start with a hash
+//     name = ferris
+//     anchor = 0, 10
+//     ---
+//                 _~^~^~_
+//             \) /  o o  \ (/
+//               '_   -   _'
+//               / '-----' \
+//
+// `anchor` is the `row, column` (both counted from 0) of the art where the
+// bubble tail ends. The tail is drawn on the two rows above the anchor, over
+// spaces only, adding empty rows on top of the art when there is no room.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::style::{Line, Part};
+use crate::width::{display_width, grapheme_width};
+
+// Extension of character files in a character directory
+pub const EXTENSION: &str = "art";
+
+// Number of rows between the bubble and the anchor
+const TAIL_LENGTH: usize = 2;
+
+// Characters compiled into the binary, the first one is the default
+const BUILTIN: [(&str, &str); 4] = [
+    ("ferris", include_str!("../characters/ferris.art")),
+    ("cow", include_str!("../characters/cow.art")),
+    ("tux", include_str!("../characters/tux.art")),
+    ("block", include_str!("../characters/block.art")),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor {
+    pub row: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Character {
+    pub name: String,
+    art: Vec<String>,
+    anchor: Anchor,
+}
+
+// A mistake in a character file, `line` and `column` start at 1
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    NotFound(String),
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::NotFound(name) => write!(f, "no character named `{}`", name),
+            LoadError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            LoadError::Parse(path, error) => write!(f, "{}:{}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::error::Error for LoadError {}
+
+impl Character {
+    // Ferris, the character used when nobody asks for another one
+    pub fn ferris() -> Character {
+        Character::builtin("ferris").expect("ferris is built in")
+    }
+
+    pub fn builtin(name: &str) -> Option<Character> {
+        BUILTIN
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, source)| Character::parse(source).expect("built-in characters are valid"))
+    }
+
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    // Finds a character by name or path.
+    // Anything that looks like a path is read directly, a plain name is first
+    // looked up as `<dir>/<name>.art` and then among the built-in characters.
+    pub fn load(name: &str, dir: Option<&Path>) -> Result<Character, LoadError> {
+        let looks_like_path = name.contains(std::path::MAIN_SEPARATOR)
+            || name.contains('/')
+            || name.ends_with(&format!(".{}", EXTENSION));
+        if looks_like_path {
+            return Character::load_file(Path::new(name));
+        }
+
+        if let Some(dir) = dir {
+            let path = dir.join(format!("{}.{}", name, EXTENSION));
+            if path.is_file() {
+                return Character::load_file(&path);
+            }
+        }
+
+        Character::builtin(name).ok_or_else(|| LoadError::NotFound(name.to_string()))
+    }
+
+    pub fn load_file(path: &Path) -> Result<Character, LoadError> {
+        let source = fs::read_to_string(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+        let mut character =
+            Character::parse(&source).map_err(|e| LoadError::Parse(path.to_path_buf(), e))?;
+        // a file without a `name` is named after the file
+        if character.name.is_empty() {
+            if let Some(stem) = path.file_stem() {
+                character.name = stem.to_string_lossy().into_owned();
+            }
+        }
+        Ok(character)
+    }
+
+    pub fn parse(source: &str) -> Result<Character, ParseError> {
+        let mut name = String::new();
+        let mut anchor = None;
+        let mut lines = source.lines().enumerate();
+
+        // the header, up to the `---` separator
+        let mut separator = None;
+        for (index, line) in lines.by_ref() {
+            let number = index + 1;
+            let trimmed = line.trim();
+            if trimmed == "---" {
+                separator = Some(number);
+                break;
+            }
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some(pair) => pair,
+                None => {
+                    return Err(error(
+                        number,
+                        line.chars().count() + 1,
+                        "expected `key = value`",
+                    ))
+                }
+            };
+            let value_column = key.chars().count() + 2 + leading_spaces(value);
+            match key.trim() {
+                "name" => name = value.trim().to_string(),
+                "anchor" => anchor = Some(parse_anchor(value, number, value_column)?),
+                other => {
+                    let column = leading_spaces(key) + 1;
+                    return Err(error(number, column, &format!("unknown key `{}`", other)));
+                }
+            }
+        }
+
+        let end = source.lines().count() + 1;
+        let separator = match separator {
+            Some(separator) => separator,
+            None => return Err(error(end, 1, "missing `---` line before the art")),
+        };
+
+        let art: Vec<String> = lines.map(|(_, line)| line.trim_end().to_string()).collect();
+        if art.is_empty() {
+            return Err(error(end, 1, "the character has no art"));
+        }
+
+        let (anchor, anchor_line) = match anchor {
+            Some(anchor) => anchor,
+            None => {
+                return Err(error(
+                    separator,
+                    1,
+                    "missing `anchor = row, column` before `---`",
+                ))
+            }
+        };
+        if anchor.row >= art.len() {
+            let rows = art.len();
+            let message = format!(
+                "anchor row {} is outside the art ({} rows)",
+                anchor.row, rows
+            );
+            return Err(error(anchor_line.0, anchor_line.1, &message));
+        }
+        if anchor.column < TAIL_LENGTH {
+            let message = format!(
+                "anchor column must be at least {} to fit the tail",
+                TAIL_LENGTH
+            );
+            return Err(error(anchor_line.0, anchor_line.1, &message));
+        }
+
+        Ok(Character { name, art, anchor })
+    }
+
+    // The art with the bubble tail drawn in, ready to go under the bubble
+    pub fn draw(&self, tail: &str) -> Vec<Line> {
+        // make room for the tail above the art
+        let missing = TAIL_LENGTH.saturating_sub(self.anchor.row);
+        let rows = std::iter::repeat_n("", missing).chain(self.art.iter().map(String::as_str));
+        let anchor_row = self.anchor.row + missing;
+
+        rows.enumerate()
+            .map(|(row, text)| {
+                // the tail goes diagonally down to the anchor
+                if row + TAIL_LENGTH >= anchor_row && row < anchor_row {
+                    let column = self.anchor.column + row - anchor_row;
+                    overlay(text, column, tail)
+                } else {
+                    Line::plain(Part::Character, text)
+                }
+            })
+            .collect()
+    }
+
+    // The character with its eyes closed, for blinking.
+    // Anything that looks like an eye becomes a dash.
+    pub fn blinking(&self) -> Character {
+        let art = self
+            .art
+            .iter()
+            .map(|row| row.replace(['o', 'O', '0', '@'], "-"))
+            .collect();
+        Character {
+            art,
+            ..self.clone()
+        }
+    }
+
+    // The character leaning one column to the right on every other row
+    // below the anchor, `phase` 0 and 1 lean opposite rows, for waving
+    pub fn waving(&self, phase: usize) -> Character {
+        let art = self
+            .art
+            .iter()
+            .enumerate()
+            .map(|(row, text)| {
+                if row > self.anchor.row && row % 2 == phase % 2 {
+                    format!(" {}", text)
+                } else {
+                    text.clone()
+                }
+            })
+            .collect();
+        Character {
+            art,
+            ..self.clone()
+        }
+    }
+}
+
+// Where the `anchor` value was found, for errors about the art itself
+type Position = (usize, usize);
+
+fn parse_anchor(value: &str, line: usize, column: usize) -> Result<(Anchor, Position), ParseError> {
+    let expected = "expected `anchor = row, column`";
+    let (row, col) = value
+        .split_once(',')
+        .ok_or_else(|| error(line, column, expected))?;
+
+    let parse = |text: &str, column: usize| {
+        text.trim()
+            .parse::<usize>()
+            .map_err(|_| error(line, column, &format!("`{}` is not a number", text.trim())))
+    };
+    let row_value = parse(row, column)?;
+    let col_column = column + row.trim_start().chars().count() + 1 + leading_spaces(col);
+    let col_value = parse(col, col_column)?;
+
+    Ok((
+        Anchor {
+            row: row_value,
+            column: col_value,
+        },
+        (line, column),
+    ))
+}
+
+fn leading_spaces(text: &str) -> usize {
+    text.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+fn error(line: usize, column: usize, message: &str) -> ParseError {
+    ParseError {
+        line,
+        column,
+        message: message.to_string(),
+    }
+}
+
+// Writes the tail `text` at display `column` of `line` if that cell is blank
+fn overlay(line: &str, column: usize, text: &str) -> Line {
+    let mut out = Line::new();
+    let width = display_width(line);
+    if width <= column {
+        out.push(Part::Character, line);
+        out.push(Part::Character, &" ".repeat(column - width));
+        out.push(Part::Border, text);
+        return out;
+    }
+
+    let mut current = 0;
+    for grapheme in line.graphemes(true) {
+        if current == column && grapheme == " " {
+            out.push(Part::Border, text);
+        } else {
+            out.push(Part::Character, grapheme);
+        }
+        current += grapheme_width(grapheme);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_characters_parse() {
+        for name in Character::builtin_names() {
+            let character = Character::builtin(name).unwrap();
+            assert_eq!(character.name, name);
+        }
+    }
+
+    #[test]
+    fn tail_is_drawn_above_the_anchor() {
+        let owl = Character::parse("anchor = 0, 4\n---\n  (o_o)\n").unwrap();
+        let lines: Vec<String> = owl.draw("\\").iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, ["  \\", "   \\", "  (o_o)"]);
+
+        // with room above the anchor the tail is drawn into the art
+        let cow = Character::builtin("cow").unwrap();
+        assert_eq!(cow.draw("o")[0].to_string(), "        o   ^__^");
+    }
+
+    #[test]
+    fn errors_point_at_line_and_column() {
+        let err = Character::parse("name = x\nanchor = 1, x\n---\nart\nart\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 13));
+
+        let err = Character::parse("# owl\n  colour = red\n---\nart\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.to_string(), "2:3: unknown key `colour`");
+
+        let err = Character::parse("anchor 0, 4\n---\nart\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 12));
+
+        let err = Character::parse("anchor = 5, 4\n---\nart\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 10));
+
+        // art without the separator is read as a header line
+        let err = Character::parse("anchor = 0, 4\n (o_o)\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 7));
+
+        let err = Character::parse("anchor = 0, 4\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+}
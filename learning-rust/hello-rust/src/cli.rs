@@ -0,0 +1,702 @@
+// Command-line handling for hello-rust.
+// We keep the parser hand-rolled: the options are few and it shows how
+// `std::env::args` hands us plain `String`s that we have to interpret ourselves.
+
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hello_rust::animate::{Motion, Timing};
+use hello_rust::character::{self, Character};
+use hello_rust::data::DataFormat;
+use hello_rust::export::Format;
+use hello_rust::figlet::Font;
+use hello_rust::render::{Alignment, BannerPosition, BorderStyle, BubbleStyle, Renderer};
+use hello_rust::style::{ColorMode, Theme};
+
+// Directory with the user's own characters when `--character-dir` is not given
+pub const CHARACTER_DIR_ENV: &str = "HELLO_RUST_CHARACTERS";
+
+pub const USAGE: &str = "\
+Usage: hello-rust [OPTIONS] [MESSAGE]...
+
+Prints MESSAGE in a speech bubble said by Ferris (or a friend). When no
+MESSAGE is given the text is read from stdin (if it is piped) or a friendly
+greeting is used.
+
+Options:
+  -w, --width <N>        wrap the text so the bubble is at most N columns wide
+  -a, --align <ALIGN>    align the text: left, center or right
+  -p, --padding <N>      spaces between the border and the text [default: 1]
+  -b, --border <STYLE>   border style: ascii, unicode or rounded
+  -t, --think            draw a thought bubble instead of a speech bubble
+  -c, --character <NAME> who is talking: a built-in or user character name,
+                         or the path of a character file
+      --character-dir <DIR>
+                         where to look for user characters (<DIR>/<NAME>.art)
+                         [default: $HELLO_RUST_CHARACTERS]
+      --list-characters  print the names of all available characters
+      --banner <WHERE>   render the message as a big FIGlet banner, inside the
+                         bubble or beside the picture
+      --font <FILE>      FIGlet font (.flf) for the banner [default: built-in]
+      --format <FORMAT>  read the message as json or csv and lay it out as a
+                         key/value block or a table
+      --animate          type the message into the bubble and let the character
+                         move (only on a terminal, otherwise the last frame
+                         is printed)
+      --motion <MOTION>  how the character moves: blink, wave or still
+                         [default: blink]
+      --frame-ms <MS>    milliseconds per typed character [default: 50]
+      --motion-ms <MS>   milliseconds per character pose [default: 300]
+      --color <WHEN>     colour the output: auto, always or never [default: auto]
+                         auto colours a terminal unless NO_COLOR is set
+      --theme <THEME>    colours to use: ferris, ocean, matrix, sunset, paper,
+                         mono or plain [default: ferris]
+      --export <FORMAT>  print the bubble as an html or svg document
+  -i, --interactive      chat with the character: every line typed is said back,
+                         type /help for commands
+      --history <FILE>   where the interactive mode keeps its history
+                         [default: $HELLO_RUST_HISTORY or ~/.hello_rust_history]
+      --compat           print exactly what the ferris_says crate used to print
+  -                      read the message from stdin
+  --                     treat every following argument as part of the message
+  -h, --help             print this help";
+
+// Where the text for the bubble comes from
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    // the words given on the command line, joined by single spaces
+    Args(String),
+    // everything piped into the program
+    Stdin,
+    // nothing was given, decide at runtime (stdin if piped, greeting otherwise)
+    Default,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub input: Input,
+    // maximum width of the text inside the bubble, `None` means "never wrap"
+    pub width: Option<usize>,
+    pub alignment: Alignment,
+    pub padding: usize,
+    pub border: BorderStyle,
+    pub bubble: BubbleStyle,
+    pub character: Option<String>,
+    pub character_dir: Option<PathBuf>,
+    pub list_characters: bool,
+    pub color: ColorMode,
+    pub theme: Theme,
+    pub export: Option<Format>,
+    pub banner: Option<BannerPosition>,
+    pub font: Option<PathBuf>,
+    pub format: Option<DataFormat>,
+    pub interactive: bool,
+    pub history: Option<PathBuf>,
+    pub animate: bool,
+    pub motion: Motion,
+    pub timing: Timing,
+    pub compat: bool,
+    pub help: bool,
+}
+
+impl Options {
+    pub fn renderer(&self) -> hello_rust::Result<Renderer> {
+        if self.compat {
+            return Ok(Renderer::compat().width(self.width));
+        }
+        let character = match &self.character {
+            Some(name) => Character::load(name, self.character_dir.as_deref())?,
+            None => Character::ferris(),
+        };
+        let mut renderer = Renderer::new()
+            .width(self.width)
+            .alignment(self.alignment)
+            .padding(self.padding)
+            .border(self.border)
+            .bubble(self.bubble)
+            .character(character);
+        if let Some(position) = self.banner {
+            let font = match &self.font {
+                Some(path) => Font::load(path)?,
+                None => Font::default_font(),
+            };
+            renderer = renderer.banner(font, position);
+        }
+        Ok(renderer)
+    }
+
+    // Built-in characters followed by the ones found in the character directory
+    pub fn character_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Character::builtin_names().map(String::from).collect();
+        let entries = self
+            .character_dir
+            .as_ref()
+            .and_then(|dir| dir.read_dir().ok());
+        for entry in entries.into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == character::EXTENSION)
+            {
+                if let Some(stem) = path.file_stem() {
+                    names.push(stem.to_string_lossy().into_owned());
+                }
+            }
+        }
+        names
+    }
+}
+
+// Parses everything after the program name.
+// Errors are returned as a message meant to be shown to the user.
+pub fn parse_args<I>(args: I) -> Result<Options, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = Options {
+        input: Input::Default,
+        width: None,
+        alignment: Alignment::Left,
+        padding: 1,
+        border: BorderStyle::Ascii,
+        bubble: BubbleStyle::Say,
+        character: None,
+        character_dir: env::var_os(CHARACTER_DIR_ENV).map(PathBuf::from),
+        list_characters: false,
+        color: ColorMode::Auto,
+        theme: Theme::default(),
+        export: None,
+        banner: None,
+        font: None,
+        format: None,
+        interactive: false,
+        history: None,
+        animate: false,
+        motion: Motion::Blink,
+        timing: Timing::default(),
+        compat: false,
+        help: false,
+    };
+    let mut words: Vec<String> = Vec::new();
+    let mut stdin = false;
+    let mut only_words = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if only_words {
+            words.push(arg);
+            continue;
+        }
+
+        // `--name=value` is the same as `--name value`
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => {
+                (name.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        // only options that read their value may be given one with `=`
+        let mut took_value = false;
+        let mut value = || {
+            took_value = true;
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("`{}` needs a value", name))
+        };
+
+        match name.as_str() {
+            "--" => only_words = true,
+            "-" => stdin = true,
+            "-h" | "--help" => options.help = true,
+            "-t" | "--think" => options.bubble = BubbleStyle::Think,
+            "--compat" => options.compat = true,
+            "--list-characters" => options.list_characters = true,
+            "-c" | "--character" => options.character = Some(value()?),
+            "--character-dir" => options.character_dir = Some(PathBuf::from(value()?)),
+            "--color" | "--colour" => options.color = parse_color(&value()?)?,
+            "--theme" => options.theme = parse_theme(&value()?)?,
+            "--export" => options.export = Some(parse_export(&value()?)?),
+            "--banner" => options.banner = Some(parse_banner(&value()?)?),
+            "--font" => options.font = Some(PathBuf::from(value()?)),
+            "--format" => options.format = Some(parse_format(&value()?)?),
+            "-i" | "--interactive" => options.interactive = true,
+            "--history" => options.history = Some(PathBuf::from(value()?)),
+            "--animate" => options.animate = true,
+            "--motion" => options.motion = parse_motion(&value()?)?,
+            "--frame-ms" => options.timing.typing = parse_millis(&value()?)?,
+            "--motion-ms" => options.timing.motion = parse_millis(&value()?)?,
+            "-w" | "--width" => options.width = Some(parse_positive(&value()?, "width")?),
+            "-p" | "--padding" => options.padding = parse_number(&value()?, "padding")?,
+            "-a" | "--align" => options.alignment = parse_alignment(&value()?)?,
+            "-b" | "--border" => options.border = parse_border(&value()?)?,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ => words.push(arg),
+        }
+        if inline_value.is_some() && !took_value {
+            return Err(format!("`{}` does not take a value", name));
+        }
+    }
+
+    if options.compat
+        && (options.character.is_some() || options.banner.is_some() || options.animate)
+    {
+        return Err(String::from(
+            "`--compat` prints exactly what ferris_says did, it cannot be combined \
+             with `--character`, `--banner` or `--animate`",
+        ));
+    }
+
+    if options.format.is_some() && options.banner.is_some() {
+        return Err(String::from(
+            "a table does not fit into a banner, `--format` cannot be combined with `--banner`",
+        ));
+    }
+
+    if options.interactive
+        && (options.compat
+            || options.animate
+            || options.format.is_some()
+            || options.export.is_some()
+            || stdin
+            || !words.is_empty())
+    {
+        return Err(String::from(
+            "`--interactive` takes its messages line by line, it cannot be combined \
+             with a message, `-`, `--compat`, `--animate`, `--format` or `--export`",
+        ));
+    }
+
+    options.input = match (stdin, words.is_empty()) {
+        (true, true) => Input::Stdin,
+        (true, false) => {
+            return Err(String::from(
+                "cannot read stdin and take a message at the same time",
+            ))
+        }
+        (false, false) => Input::Args(words.join(" ")),
+        (false, true) => Input::Default,
+    };
+
+    Ok(options)
+}
+
+fn parse_number(value: &str, what: &str) -> Result<usize, String> {
+    value
+        .parse::<usize>()
+        .map_err(|_| format!("invalid {} `{}`, expected a number", what, value))
+}
+
+fn parse_positive(value: &str, what: &str) -> Result<usize, String> {
+    match parse_number(value, what)? {
+        0 => Err(format!(
+            "invalid {} `{}`, expected a positive number",
+            what, value
+        )),
+        number => Ok(number),
+    }
+}
+
+fn parse_alignment(value: &str) -> Result<Alignment, String> {
+    match value {
+        "left" => Ok(Alignment::Left),
+        "center" | "centre" => Ok(Alignment::Center),
+        "right" => Ok(Alignment::Right),
+        _ => Err(format!(
+            "unknown alignment `{}`, expected left, center or right",
+            value
+        )),
+    }
+}
+
+fn parse_border(value: &str) -> Result<BorderStyle, String> {
+    match value {
+        "ascii" => Ok(BorderStyle::Ascii),
+        "unicode" => Ok(BorderStyle::Unicode),
+        "rounded" => Ok(BorderStyle::Rounded),
+        _ => Err(format!(
+            "unknown border `{}`, expected ascii, unicode or rounded",
+            value
+        )),
+    }
+}
+
+fn parse_color(value: &str) -> Result<ColorMode, String> {
+    match value {
+        "auto" => Ok(ColorMode::Auto),
+        "always" => Ok(ColorMode::Always),
+        "never" => Ok(ColorMode::Never),
+        _ => Err(format!(
+            "unknown colour mode `{}`, expected auto, always or never",
+            value
+        )),
+    }
+}
+
+fn parse_theme(value: &str) -> Result<Theme, String> {
+    Theme::named(value).ok_or_else(|| {
+        let names: Vec<&str> = Theme::names().collect();
+        format!(
+            "unknown theme `{}`, expected one of {}",
+            value,
+            names.join(", ")
+        )
+    })
+}
+
+fn parse_export(value: &str) -> Result<Format, String> {
+    match value {
+        "html" => Ok(Format::Html),
+        "svg" => Ok(Format::Svg),
+        _ => Err(format!(
+            "unknown export format `{}`, expected html or svg",
+            value
+        )),
+    }
+}
+
+fn parse_banner(value: &str) -> Result<BannerPosition, String> {
+    match value {
+        "inside" => Ok(BannerPosition::Inside),
+        "beside" => Ok(BannerPosition::Beside),
+        _ => Err(format!(
+            "unknown banner position `{}`, expected inside or beside",
+            value
+        )),
+    }
+}
+
+fn parse_format(value: &str) -> Result<DataFormat, String> {
+    match value {
+        "json" => Ok(DataFormat::Json),
+        "csv" => Ok(DataFormat::Csv),
+        _ => Err(format!("unknown format `{}`, expected json or csv", value)),
+    }
+}
+
+fn parse_motion(value: &str) -> Result<Motion, String> {
+    match value {
+        "blink" => Ok(Motion::Blink),
+        "wave" => Ok(Motion::Wave),
+        "still" => Ok(Motion::Still),
+        _ => Err(format!(
+            "unknown motion `{}`, expected blink, wave or still",
+            value
+        )),
+    }
+}
+
+fn parse_millis(value: &str) -> Result<Duration, String> {
+    parse_number(value, "duration").map(|ms| Duration::from_millis(ms as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn error(args: &[&str]) -> String {
+        parse(args).unwrap_err()
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.input, Input::Default);
+        assert_eq!(options.width, None);
+        assert_eq!(options.alignment, Alignment::Left);
+        assert_eq!(options.padding, 1);
+        assert_eq!(options.border, BorderStyle::Ascii);
+        assert_eq!(options.bubble, BubbleStyle::Say);
+        assert_eq!(options.color, ColorMode::Auto);
+        assert_eq!(options.theme, Theme::default());
+        assert_eq!(options.motion, Motion::Blink);
+        assert_eq!(options.timing, Timing::default());
+        assert!(!options.compat && !options.animate && !options.interactive && !options.help);
+    }
+
+    #[test]
+    fn words_become_the_message() {
+        let options = parse(&["Hello", "fellow", "Rustaceans!"]).unwrap();
+        assert_eq!(
+            options.input,
+            Input::Args(String::from("Hello fellow Rustaceans!"))
+        );
+
+        // after `--` even things that look like options are words
+        let options = parse(&["--", "-w", "-", "--think"]).unwrap();
+        assert_eq!(options.input, Input::Args(String::from("-w - --think")));
+        assert_eq!(options.width, None);
+        assert_eq!(options.bubble, BubbleStyle::Say);
+    }
+
+    #[test]
+    fn a_dash_reads_stdin() {
+        assert_eq!(parse(&["-"]).unwrap().input, Input::Stdin);
+        assert_eq!(parse(&["-w", "20", "-"]).unwrap().input, Input::Stdin);
+        assert_eq!(
+            error(&["-", "hello"]),
+            "cannot read stdin and take a message at the same time"
+        );
+        assert_eq!(
+            error(&["hello", "-"]),
+            "cannot read stdin and take a message at the same time"
+        );
+    }
+
+    #[test]
+    fn short_long_and_inline_values() {
+        for args in [&["-w", "12"][..], &["--width", "12"], &["--width=12"]] {
+            assert_eq!(parse(args).unwrap().width, Some(12), "{:?}", args);
+        }
+        // only long options take `=`, and only the first `=` splits
+        assert_eq!(
+            parse(&["--character=a=b"]).unwrap().character.as_deref(),
+            Some("a=b")
+        );
+        assert_eq!(
+            parse(&["a=b"]).unwrap().input,
+            Input::Args(String::from("a=b"))
+        );
+        assert_eq!(error(&["-w=12"]), "unknown option `-w=12`");
+    }
+
+    #[test]
+    fn flags_do_not_take_values() {
+        for (arg, flag) in [
+            ("--compat=no", "--compat"),
+            ("--think=off", "--think"),
+            ("--help=x", "--help"),
+            ("--animate=false", "--animate"),
+            ("--interactive=0", "--interactive"),
+            ("--list-characters=all", "--list-characters"),
+            ("--=foo", "--"),
+        ] {
+            assert_eq!(
+                error(&[arg]),
+                format!("`{}` does not take a value", flag),
+                "{}",
+                arg
+            );
+        }
+        // after `--` it is only a word
+        assert_eq!(
+            parse(&["--", "--compat=no"]).unwrap().input,
+            Input::Args(String::from("--compat=no"))
+        );
+    }
+
+    #[test]
+    fn widths_and_paddings() {
+        assert_eq!(
+            error(&["-w", "0"]),
+            "invalid width `0`, expected a positive number"
+        );
+        assert_eq!(
+            error(&["--width", "wide"]),
+            "invalid width `wide`, expected a number"
+        );
+        assert_eq!(
+            error(&["-w", "-3"]),
+            "invalid width `-3`, expected a number"
+        );
+        assert_eq!(parse(&["-p", "0"]).unwrap().padding, 0);
+        assert_eq!(parse(&["--padding=3"]).unwrap().padding, 3);
+        assert_eq!(
+            error(&["-p", "lots"]),
+            "invalid padding `lots`, expected a number"
+        );
+    }
+
+    #[test]
+    fn missing_values() {
+        assert_eq!(error(&["-w"]), "`-w` needs a value");
+        assert_eq!(error(&["hello", "--width"]), "`--width` needs a value");
+        assert_eq!(error(&["--theme"]), "`--theme` needs a value");
+        assert_eq!(error(&["--history"]), "`--history` needs a value");
+    }
+
+    #[test]
+    fn unknown_options() {
+        assert_eq!(error(&["--wdith", "3"]), "unknown option `--wdith`");
+        assert_eq!(error(&["-x"]), "unknown option `-x`");
+        assert_eq!(error(&["--nope=1"]), "unknown option `--nope=1`");
+    }
+
+    #[test]
+    fn bubble_options() {
+        let options = parse(&["-a", "center", "-b", "rounded", "-t"]).unwrap();
+        assert_eq!(options.alignment, Alignment::Center);
+        assert_eq!(options.border, BorderStyle::Rounded);
+        assert_eq!(options.bubble, BubbleStyle::Think);
+        assert_eq!(
+            parse(&["--align=centre"]).unwrap().alignment,
+            Alignment::Center
+        );
+        assert_eq!(
+            parse(&["--align", "right"]).unwrap().alignment,
+            Alignment::Right
+        );
+        assert_eq!(
+            parse(&["--border", "unicode", "--think"]).unwrap().border,
+            BorderStyle::Unicode
+        );
+        assert_eq!(
+            error(&["-a", "middle"]),
+            "unknown alignment `middle`, expected left, center or right"
+        );
+        assert_eq!(
+            error(&["-b", "double"]),
+            "unknown border `double`, expected ascii, unicode or rounded"
+        );
+    }
+
+    #[test]
+    fn characters() {
+        let options = parse(&["-c", "crab", "--character-dir", "art"]).unwrap();
+        assert_eq!(options.character.as_deref(), Some("crab"));
+        assert_eq!(options.character_dir, Some(PathBuf::from("art")));
+        assert!(parse(&["--list-characters"]).unwrap().list_characters);
+    }
+
+    #[test]
+    fn colours_and_themes() {
+        assert_eq!(
+            parse(&["--color", "always"]).unwrap().color,
+            ColorMode::Always
+        );
+        assert_eq!(parse(&["--colour=never"]).unwrap().color, ColorMode::Never);
+        assert_eq!(
+            error(&["--color", "sometimes"]),
+            "unknown colour mode `sometimes`, expected auto, always or never"
+        );
+        assert_eq!(
+            parse(&["--theme", "ocean"]).unwrap().theme,
+            Theme::named("ocean").unwrap()
+        );
+        let names: Vec<&str> = Theme::names().collect();
+        assert_eq!(
+            error(&["--theme", "neon"]),
+            format!("unknown theme `neon`, expected one of {}", names.join(", "))
+        );
+    }
+
+    #[test]
+    fn export_banner_and_format() {
+        assert_eq!(
+            parse(&["--export", "svg"]).unwrap().export,
+            Some(Format::Svg)
+        );
+        assert_eq!(
+            parse(&["--export=html"]).unwrap().export,
+            Some(Format::Html)
+        );
+        assert_eq!(
+            error(&["--export", "pdf"]),
+            "unknown export format `pdf`, expected html or svg"
+        );
+
+        let options = parse(&["--banner", "beside", "--font", "big.flf"]).unwrap();
+        assert_eq!(options.banner, Some(BannerPosition::Beside));
+        assert_eq!(options.font, Some(PathBuf::from("big.flf")));
+        assert_eq!(
+            error(&["--banner", "above"]),
+            "unknown banner position `above`, expected inside or beside"
+        );
+
+        assert_eq!(
+            parse(&["--format", "csv"]).unwrap().format,
+            Some(DataFormat::Csv)
+        );
+        assert_eq!(
+            parse(&["--format=json"]).unwrap().format,
+            Some(DataFormat::Json)
+        );
+        assert_eq!(
+            error(&["--format", "yaml"]),
+            "unknown format `yaml`, expected json or csv"
+        );
+        assert_eq!(
+            error(&["--format", "json", "--banner", "inside"]),
+            "a table does not fit into a banner, `--format` cannot be combined with `--banner`"
+        );
+    }
+
+    #[test]
+    fn animation() {
+        let options = parse(&[
+            "--animate",
+            "--motion",
+            "wave",
+            "--frame-ms",
+            "10",
+            "--motion-ms=500",
+        ])
+        .unwrap();
+        assert!(options.animate);
+        assert_eq!(options.motion, Motion::Wave);
+        assert_eq!(options.timing.typing, Duration::from_millis(10));
+        assert_eq!(options.timing.motion, Duration::from_millis(500));
+        assert_eq!(parse(&["--motion=still"]).unwrap().motion, Motion::Still);
+        assert_eq!(
+            error(&["--motion", "dance"]),
+            "unknown motion `dance`, expected blink, wave or still"
+        );
+        assert_eq!(
+            error(&["--frame-ms", "fast"]),
+            "invalid duration `fast`, expected a number"
+        );
+    }
+
+    #[test]
+    fn interactive_and_history() {
+        let options = parse(&["-i", "--history", "chat.txt"]).unwrap();
+        assert!(options.interactive);
+        assert_eq!(options.history, Some(PathBuf::from("chat.txt")));
+        assert!(
+            parse(&["--interactive", "--theme=paper"])
+                .unwrap()
+                .interactive
+        );
+
+        let conflict = "`--interactive` takes its messages line by line, it cannot be combined \
+                        with a message, `-`, `--compat`, `--animate`, `--format` or `--export`";
+        for args in [
+            &["-i", "hello"][..],
+            &["-i", "-"],
+            &["-i", "--compat"],
+            &["-i", "--animate"],
+            &["-i", "--format", "json"],
+            &["-i", "--export", "html"],
+        ] {
+            assert_eq!(error(args), conflict, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn compat() {
+        let options = parse(&["--compat", "-w", "20", "hi"]).unwrap();
+        assert!(options.compat);
+        assert_eq!(options.width, Some(20));
+
+        let conflict = "`--compat` prints exactly what ferris_says did, it cannot be combined \
+                        with `--character`, `--banner` or `--animate`";
+        for args in [
+            &["--compat", "-c", "crab"][..],
+            &["--compat", "--banner", "inside"],
+            &["--compat", "--animate"],
+        ] {
+            assert_eq!(error(args), conflict, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn help() {
+        assert!(parse(&["-h"]).unwrap().help);
+        assert!(parse(&["hello", "--help"]).unwrap().help);
+    }
+}
@@ -0,0 +1,415 @@
+// Structured data in a bubble.
+// JSON objects become a block of `key: value` lines (nested objects get
+// dotted keys), a JSON array of objects or a CSV file becomes a table with
+// the first row as header. The result is plain text that the renderer puts
+// into the bubble like any other message.
+//
+// With a width every line is kept inside it: values wrap inside their column
+// and the widest table columns are narrowed first.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::width::{display_width, pad_to};
+use crate::wrap::{max_width, wrap};
+
+// Columns are never narrowed below this, a table wider than the bubble
+// wraps instead of becoming a column of single letters
+const MIN_COLUMN: usize = 4;
+
+// Between two table columns and between a key and its value
+const COLUMN_SEPARATOR: &str = " | ";
+const KEY_SEPARATOR: &str = ": ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Debug)]
+pub enum DataError {
+    Json(serde_json::Error),
+    // `line` starts at 1
+    Csv { line: usize, message: String },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataError::Json(error) => write!(f, "invalid JSON: {}", error),
+            DataError::Csv { line, message } => {
+                write!(f, "invalid CSV on line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+// Lays `input` out as text lines no wider than `width`, `None` never wraps
+pub fn layout(input: &str, format: DataFormat, width: Option<usize>) -> Result<String, DataError> {
+    let lines = match format {
+        DataFormat::Json => {
+            let value: Value = serde_json::from_str(input).map_err(DataError::Json)?;
+            layout_json(&value, width)
+        }
+        DataFormat::Csv => {
+            let mut records = parse_csv(input)?;
+            if records.is_empty() {
+                return Err(DataError::Csv {
+                    line: 1,
+                    message: String::from("there is no header row"),
+                });
+            }
+            let header = records.remove(0);
+            table(&header, &records, width)
+        }
+    };
+    Ok(lines.join("\n"))
+}
+
+fn layout_json(value: &Value, width: Option<usize>) -> Vec<String> {
+    match value {
+        // a list of records, like the rows of a CSV file
+        Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+            let mut header: Vec<String> = Vec::new();
+            for item in items.iter().filter_map(Value::as_object) {
+                for key in item.keys() {
+                    if !header.contains(key) {
+                        header.push(key.clone());
+                    }
+                }
+            }
+            let rows: Vec<Vec<String>> = items
+                .iter()
+                .filter_map(Value::as_object)
+                .map(|item| header.iter().map(|key| cell(item.get(key))).collect())
+                .collect();
+            table(&header, &rows, width)
+        }
+        Value::Object(object) => {
+            let mut entries = Vec::new();
+            flatten_object("", object, &mut entries);
+            key_values(&entries, width)
+        }
+        // a bare value or a list of values: one per line
+        Value::Array(items) => items
+            .iter()
+            .flat_map(|item| wrap(&scalar(item), width))
+            .collect(),
+        other => wrap(&scalar(other), width),
+    }
+}
+
+// Collects `key: value` pairs, `prefix.key` for nested objects and
+// `prefix[i]` for arrays that hold more than plain values
+fn flatten_object(prefix: &str, object: &Map<String, Value>, entries: &mut Vec<(String, String)>) {
+    if object.is_empty() && !prefix.is_empty() {
+        entries.push((prefix.to_string(), String::from("{}")));
+    }
+    for (key, value) in object {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        flatten(key, value, entries);
+    }
+}
+
+fn flatten(key: String, value: &Value, entries: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => flatten_object(&key, object, entries),
+        Value::Array(items) if items.iter().any(|item| item.is_object() || item.is_array()) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(format!("{}[{}]", key, i), item, entries);
+            }
+        }
+        other => entries.push((key, scalar(other))),
+    }
+}
+
+// A value as text: strings without quotes, lists of values joined by commas
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) if items.is_empty() => String::from("[]"),
+        Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+// The text of a table cell, nested structures stay compact JSON
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None => String::new(),
+        Some(value @ Value::Object(_)) => value.to_string(),
+        Some(value) => scalar(value),
+    }
+}
+
+fn key_values(entries: &[(String, String)], width: Option<usize>) -> Vec<String> {
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| display_width(key))
+        .max()
+        .unwrap_or(0);
+    let indent = key_width + KEY_SEPARATOR.len();
+    let value_width = width.map(|width| width.saturating_sub(indent).max(MIN_COLUMN));
+
+    let mut lines = Vec::new();
+    for (key, value) in entries {
+        for (i, part) in wrap(value, value_width).iter().enumerate() {
+            if i == 0 {
+                lines.push(format!(
+                    "{}{}{}",
+                    pad_to(key, key_width),
+                    KEY_SEPARATOR,
+                    part
+                ));
+            } else {
+                lines.push(format!("{}{}", " ".repeat(indent), part));
+            }
+        }
+    }
+    // the same width everywhere, so aligning the bubble text does not move
+    // the values out of their column
+    let block_width = max_width(&lines);
+    lines.iter().map(|line| pad_to(line, block_width)).collect()
+}
+
+fn table(header: &[String], rows: &[Vec<String>], width: Option<usize>) -> Vec<String> {
+    let columns = header
+        .len()
+        .max(rows.iter().map(Vec::len).max().unwrap_or(0));
+    let text = |row: &[String], column: usize| row.get(column).cloned().unwrap_or_default();
+
+    let mut widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            std::iter::once(header)
+                .chain(rows.iter().map(Vec::as_slice))
+                .map(|row| max_width(&wrap(&text(row, column), None)))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    if let Some(width) = width {
+        narrow(&mut widths, width);
+    }
+
+    let mut lines = Vec::new();
+    lines.extend(table_row(header, &widths));
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-"),
+    );
+    for row in rows {
+        lines.extend(table_row(row, &widths));
+    }
+    lines
+}
+
+// Takes a column off the widest column until the table fits `width`
+fn narrow(widths: &mut [usize], width: usize) {
+    let separators = COLUMN_SEPARATOR.len() * widths.len().saturating_sub(1);
+    while widths.iter().sum::<usize>() + separators > width {
+        let widest = match widths.iter().enumerate().max_by_key(|(_, width)| **width) {
+            Some((widest, &width)) if width > MIN_COLUMN => widest,
+            _ => break,
+        };
+        widths[widest] -= 1;
+    }
+}
+
+// One table row, as many lines as its tallest wrapped cell
+fn table_row(row: &[String], widths: &[usize]) -> Vec<String> {
+    let cells: Vec<Vec<String>> = widths
+        .iter()
+        .enumerate()
+        .map(|(column, width)| wrap(row.get(column).map_or("", String::as_str), Some(*width)))
+        .collect();
+    let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+    (0..height)
+        .map(|line| {
+            cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| pad_to(cell.get(line).map_or("", String::as_str), *width))
+                .collect::<Vec<_>>()
+                .join(COLUMN_SEPARATOR)
+        })
+        .collect()
+}
+
+// Records of a CSV file as described in RFC 4180: fields are separated by
+// commas, quoted fields may hold commas, line breaks and doubled quotes
+fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, DataError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    // where the open quote is, for the error when it is never closed
+    let mut quoted_since = None;
+    // the field was quoted and its closing quote has been read
+    let mut closed = false;
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted_since.is_some() {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    quoted_since = None;
+                    closed = true;
+                }
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() && !closed => quoted_since = Some(line),
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                closed = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                closed = false;
+                line += 1;
+            }
+            _ if closed => {
+                return Err(DataError::Csv {
+                    line,
+                    message: format!("unexpected `{}` after a closing quote", c),
+                })
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if let Some(start) = quoted_since {
+        return Err(DataError::Csv {
+            line: start,
+            message: String::from("the quoted field is never closed"),
+        });
+    }
+    if !field.is_empty() || !record.is_empty() || closed {
+        record.push(field);
+        records.push(record);
+    }
+    // blank lines carry no record
+    records.retain(|record| !(record.len() == 1 && record[0].is_empty()));
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str, format: DataFormat, width: Option<usize>) -> Vec<String> {
+        layout(input, format, width)
+            .unwrap()
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn json_objects_become_key_value_blocks() {
+        let block = r#"{"height": 1024, "hash": "00ab", "miner": {"name": "ferris"},
+                        "tags": ["a", "b"], "txs": [{"id": 1}]}"#;
+        assert_eq!(
+            lines(block, DataFormat::Json, None),
+            [
+                "height    : 1024",
+                "hash      : 00ab",
+                "miner.name: ferris",
+                "tags      : a, b",
+                "txs[0].id : 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_values_wrap_under_their_column() {
+        let summary = r#"{"status": "ok", "message": "all seven crates built fine"}"#;
+        assert_eq!(
+            lines(summary, DataFormat::Json, Some(20)),
+            [
+                "status : ok",
+                "message: all seven",
+                "         crates",
+                "         built fine",
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_of_objects_and_csv_become_tables() {
+        let json = r#"[{"crate": "ownership", "ok": true}, {"crate": "variables", "time": 1.5}]"#;
+        let csv = "crate,ok,time\nownership,true,\nvariables,,1.5\n";
+        let expected = [
+            "crate     | ok   | time",
+            "----------+------+-----",
+            "ownership | true |",
+            "variables |      | 1.5",
+        ];
+        assert_eq!(lines(json, DataFormat::Json, None), expected);
+        assert_eq!(lines(csv, DataFormat::Csv, None), expected);
+    }
+
+    #[test]
+    fn the_widest_column_is_narrowed_first() {
+        let csv = "name,description\nrope,a balanced tree of string chunks\n";
+        assert_eq!(
+            lines(csv, DataFormat::Csv, Some(20)),
+            [
+                "name | description",
+                "-----+--------------",
+                "rope | a balanced",
+                "     | tree of",
+                "     | string chunks",
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_quotes_hold_commas_quotes_and_line_breaks() {
+        let records = parse_csv("a,b\r\n\"x, y\",\"say \"\"hi\"\"\nthere\"\n").unwrap();
+        assert_eq!(records, [vec!["a", "b"], vec!["x, y", "say \"hi\"\nthere"]]);
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = layout("a\n\"open\n\n", DataFormat::Csv, None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid CSV on line 2: the quoted field is never closed"
+        );
+
+        let error = layout("a\n\"b\"c\n", DataFormat::Csv, None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid CSV on line 2: unexpected `c` after a closing quote"
+        );
+
+        let error = layout("{\"a\": }", DataFormat::Json, None).unwrap_err();
+        assert!(error.to_string().starts_with("invalid JSON: "));
+    }
+}
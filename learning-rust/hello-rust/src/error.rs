@@ -0,0 +1,71 @@
+// The one error type of the library.
+// Everything that can go wrong while building a renderer or writing a bubble
+// ends up here, so callers can use `?` and decide themselves how to report it.
+
+use std::fmt;
+use std::io;
+
+use crate::character::LoadError;
+use crate::data::DataError;
+use crate::figlet::FontError;
+
+#[derive(Debug)]
+pub enum Error {
+    // writing the output (or reading the input) failed
+    Io(io::Error),
+    // a character could not be found or its file is broken
+    Character(LoadError),
+    // a FIGlet font could not be read or parsed
+    Font(FontError),
+    // the JSON or CSV given with `--format` is malformed
+    Data(DataError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => error.fmt(f),
+            Error::Character(error) => error.fmt(f),
+            Error::Font(error) => error.fmt(f),
+            Error::Data(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Character(error) => Some(error),
+            Error::Font(error) => Some(error),
+            Error::Data(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+impl From<LoadError> for Error {
+    fn from(error: LoadError) -> Error {
+        Error::Character(error)
+    }
+}
+
+impl From<FontError> for Error {
+    fn from(error: FontError) -> Error {
+        Error::Font(error)
+    }
+}
+
+impl From<DataError> for Error {
+    fn from(error: DataError) -> Error {
+        Error::Data(error)
+    }
+}
+
+// Shorthand used all over the library
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,147 @@
+// Exports a rendered bubble as HTML or SVG, for wikis and slides.
+// Both keep the monospaced layout of the terminal and use the theme colours.
+
+use crate::style::{Line, Style, Theme};
+use crate::width::display_width;
+
+// Size of one terminal cell in the SVG, in pixels
+const CELL_WIDTH: f32 = 8.4;
+const LINE_HEIGHT: usize = 18;
+const FONT_SIZE: usize = 14;
+const MARGIN: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Html,
+    Svg,
+}
+
+const BACKGROUND: &str = "#1e1e1e";
+const FOREGROUND: &str = "#e5e5e5";
+
+// A `<pre>` block with a `<span>` for every styled part
+pub fn html(lines: &[Line], theme: &Theme) -> String {
+    let mut out = format!(
+        "<pre class=\"hello-rust\" style=\"background: {}; color: {}; padding: 1em; \
+         font-family: monospace; line-height: 1.2;\">",
+        BACKGROUND, FOREGROUND
+    );
+    for line in lines {
+        for span in &line.spans {
+            let style = css(theme.style(span.part));
+            if style.is_empty() {
+                out.push_str(&escape(&span.text));
+            } else {
+                out.push_str(&format!(
+                    "<span style=\"{}\">{}</span>",
+                    style,
+                    escape(&span.text)
+                ));
+            }
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n");
+    out
+}
+
+// A standalone SVG image, one `<text>` element per line
+pub fn svg(lines: &[Line], theme: &Theme) -> String {
+    let columns = lines
+        .iter()
+        .map(|line| display_width(&line.to_string()))
+        .max()
+        .unwrap_or(0);
+    let width = (columns as f32 * CELL_WIDTH).ceil() as usize + 2 * MARGIN;
+    let height = lines.len() * LINE_HEIGHT + 2 * MARGIN;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+    out.push_str(&format!(
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        BACKGROUND
+    ));
+    out.push_str(&format!(
+        "<g font-family=\"monospace\" font-size=\"{}\" fill=\"{}\" xml:space=\"preserve\">\n",
+        FONT_SIZE, FOREGROUND
+    ));
+    for (i, line) in lines.iter().enumerate() {
+        // `y` is the baseline, roughly four fifths down the line
+        let y = MARGIN + i * LINE_HEIGHT + LINE_HEIGHT * 4 / 5;
+        out.push_str(&format!("<text x=\"{}\" y=\"{}\">", MARGIN, y));
+        for span in &line.spans {
+            let style = theme.style(span.part);
+            let mut attributes = String::new();
+            if let Some(color) = style.color {
+                attributes.push_str(&format!(" fill=\"{}\"", color.hex()));
+            }
+            if style.bold {
+                attributes.push_str(" font-weight=\"bold\"");
+            }
+            out.push_str(&format!(
+                "<tspan{}>{}</tspan>",
+                attributes,
+                escape(&span.text)
+            ));
+        }
+        out.push_str("</text>\n");
+    }
+    out.push_str("</g>\n</svg>\n");
+    out
+}
+
+fn css(style: Style) -> String {
+    let mut rules = Vec::new();
+    if let Some(color) = style.color {
+        rules.push(format!("color: {}", color.hex()));
+    }
+    if style.bold {
+        rules.push(String::from("font-weight: bold"));
+    }
+    rules.join("; ")
+}
+
+// The characters that mean something in HTML and XML
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::Part;
+
+    #[test]
+    fn html_escapes_the_bubble() {
+        let mut line = Line::plain(Part::Border, "< ");
+        line.push(Part::Text, "a & b");
+        line.push(Part::Border, " >");
+        let html = html(&[line], &Theme::named("plain").unwrap());
+        assert!(html.contains("&lt; a &amp; b &gt;\n"), "{}", html);
+    }
+
+    #[test]
+    fn svg_has_a_text_element_per_line() {
+        let lines = [
+            Line::plain(Part::Text, "one"),
+            Line::plain(Part::Character, "two"),
+        ];
+        let svg = svg(&lines, &Theme::default());
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(svg.contains("fill=\"#f74c00\""), "{}", svg);
+    }
+}
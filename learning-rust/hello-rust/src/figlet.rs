@@ -0,0 +1,561 @@
+// Large banner text from FIGlet fonts.
+//
+// A FIGlet font (`.flf`) starts with a header line like
+//
+//     flf2a$ 4 3 8 63 3 0 191 0
+//
+// `flf2a` is the signature, directly followed by the "hardblank" character,
+// then the height of a letter, the baseline, the longest line, the old layout,
+// the number of comment lines, the print direction and the full layout.
+// After the comments every letter from ' ' to '~' follows, then seven German
+// letters and finally optional "code tagged" letters introduced by their code.
+// Each letter is `height` lines that end with an end mark (usually `@`).
+//
+// Letters are glued together by moving the next one to the left as far as it
+// goes. With "smushing" the touching sub-characters may even overlap when
+// one of the smushing rules says what the overlap looks like.
+// Hardblanks look like spaces in the output but are never overlapped,
+// they keep letters (and words) apart.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const SIGNATURE: &str = "flf2a";
+
+// The font compiled into the binary
+const DEFAULT_FONT: &str = include_str!("../fonts/rusty.flf");
+
+// The German letters every font carries after '~', in this order
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+// Bits of the full layout, only the horizontal ones matter here
+const SMUSH_EQUAL: u32 = 1;
+const SMUSH_UNDERSCORE: u32 = 2;
+const SMUSH_HIERARCHY: u32 = 4;
+const SMUSH_PAIR: u32 = 8;
+const SMUSH_BIG_X: u32 = 16;
+const SMUSH_HARDBLANK: u32 = 32;
+const KERNING: u32 = 64;
+const SMUSHING: u32 = 128;
+const HORIZONTAL_RULES: u32 = 63;
+
+#[derive(Debug, Clone)]
+pub struct Font {
+    hardblank: char,
+    height: usize,
+    // horizontal layout bits, see the constants above
+    layout: u32,
+    glyphs: HashMap<char, Vec<Vec<char>>>,
+}
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(PathBuf, io::Error),
+    // a problem in the font file, `line` starts at 1
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            FontError::Parse { line, message } => write!(f, "font line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl Font {
+    pub fn default_font() -> Font {
+        Font::parse(DEFAULT_FONT).expect("the built-in font is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Font, FontError> {
+        let source = fs::read_to_string(path).map_err(|e| FontError::Io(path.to_path_buf(), e))?;
+        Font::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<Font, FontError> {
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let (_, header) = lines
+            .next()
+            .ok_or_else(|| parse_error(1, "the font is empty"))?;
+
+        let rest = header
+            .strip_prefix(SIGNATURE)
+            .ok_or_else(|| parse_error(1, "not a FIGlet font, missing `flf2a` signature"))?;
+        let mut rest = rest.chars();
+        let hardblank = rest
+            .next()
+            .ok_or_else(|| parse_error(1, "missing hardblank"))?;
+        let numbers: Vec<i64> = rest
+            .as_str()
+            .split_whitespace()
+            .map(|n| {
+                n.parse()
+                    .map_err(|_| parse_error(1, &format!("`{}` is not a number", n)))
+            })
+            .collect::<Result<_, _>>()?;
+        if numbers.len() < 5 {
+            let message = "the header needs height, baseline, max length, layout and comment lines";
+            return Err(parse_error(1, message));
+        }
+
+        let height = usize::try_from(numbers[0])
+            .ok()
+            .filter(|height| *height > 0)
+            .ok_or_else(|| parse_error(1, "the height must be positive"))?;
+        let old_layout = numbers[3];
+        let comment_lines = usize::try_from(numbers[4])
+            .map_err(|_| parse_error(1, "negative number of comment lines"))?;
+        let layout = match numbers.get(6) {
+            Some(full) => *full as u32 & (HORIZONTAL_RULES | KERNING | SMUSHING),
+            // fonts older than the full layout field only have the old one
+            None if old_layout < 0 => 0,
+            None if old_layout == 0 => KERNING,
+            None => old_layout as u32 & HORIZONTAL_RULES | SMUSHING,
+        };
+
+        for _ in 0..comment_lines {
+            lines.next();
+        }
+
+        // errors about a truncated font point just past the last line
+        let end = source.lines().count() + 1;
+        let mut glyphs = HashMap::new();
+        let required = (' '..='~').chain(DEUTSCH);
+        for code in required {
+            match read_glyph(&mut lines, height, end)? {
+                Some(glyph) => glyphs.insert(code, glyph),
+                // some fonts leave out the German letters
+                None if DEUTSCH.contains(&code) => break,
+                None => {
+                    return Err(parse_error(
+                        end,
+                        &format!("the font ends before `{}`", code),
+                    ))
+                }
+            };
+        }
+
+        // code tagged letters: a line with the code, then the letter
+        while let Some((number, tag)) = lines.next() {
+            if tag.trim().is_empty() {
+                continue;
+            }
+            let code = tag.split_whitespace().next().unwrap_or("");
+            let c = parse_code(code).ok_or_else(|| {
+                parse_error(number, &format!("invalid character code `{}`", code))
+            })?;
+            match read_glyph(&mut lines, height, end)? {
+                Some(glyph) => {
+                    if let Some(c) = c {
+                        glyphs.insert(c, glyph);
+                    }
+                }
+                None => return Err(parse_error(end, "the font ends inside a letter")),
+            }
+        }
+
+        Ok(Font {
+            hardblank,
+            height,
+            layout,
+            glyphs,
+        })
+    }
+
+    // Renders one banner per line of `text`, stacked on top of each other
+    pub fn render(&self, text: &str) -> Vec<String> {
+        self.render_wrapped(text, None)
+    }
+
+    // Like `render`, but starts a new banner line before a word that would
+    // make the banner wider than `width` columns
+    pub fn render_wrapped(&self, text: &str, width: Option<usize>) -> Vec<String> {
+        let mut out = Vec::new();
+        for line in text.lines() {
+            let mut banner = Banner::new(self.height);
+            for word in line.split(' ') {
+                let mut candidate = banner.clone();
+                if !banner.is_empty() {
+                    self.add(&mut candidate, ' ');
+                }
+                self.add_word(&mut candidate, word);
+
+                let too_wide = width.is_some_and(|width| candidate.width() > width);
+                if too_wide && !banner.is_empty() {
+                    out.extend(self.finish(banner));
+                    banner = Banner::new(self.height);
+                    self.add_word(&mut banner, word);
+                } else {
+                    banner = candidate;
+                }
+            }
+            out.extend(self.finish(banner));
+        }
+        out
+    }
+
+    fn add_word(&self, banner: &mut Banner, word: &str) {
+        for c in word.chars() {
+            self.add(banner, c);
+        }
+    }
+
+    // Turns hardblanks into spaces, the banner is done
+    fn finish(&self, banner: Banner) -> Vec<String> {
+        let hardblank = self.hardblank;
+        banner
+            .rows
+            .into_iter()
+            .map(|row| {
+                let row: String = row
+                    .into_iter()
+                    .map(|c| if c == hardblank { ' ' } else { c })
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    // Glues the letter `c` to the right of `banner`, letters the font does
+    // not have are left out
+    fn add(&self, banner: &mut Banner, c: char) {
+        let glyph = match self.glyphs.get(&c) {
+            Some(glyph) => glyph,
+            None => return,
+        };
+        let glyph_width = glyph.first().map_or(0, Vec::len);
+        let overlap = self.overlap(banner, glyph, glyph_width);
+        let banner_width = banner.width();
+
+        for (row, letter) in banner.rows.iter_mut().zip(glyph) {
+            for (k, &right) in letter.iter().enumerate() {
+                if k < overlap {
+                    let column = banner_width - overlap + k;
+                    let left = row[column];
+                    let smushed = self.smush(left, right, banner.previous_width, glyph_width);
+                    row[column] = smushed.unwrap_or(right);
+                } else {
+                    row.push(right);
+                }
+            }
+        }
+        banner.previous_width = glyph_width;
+    }
+
+    // How many columns the next letter can be moved into the banner
+    fn overlap(&self, banner: &Banner, glyph: &[Vec<char>], glyph_width: usize) -> usize {
+        if self.layout & (KERNING | SMUSHING) == 0 {
+            return 0;
+        }
+
+        let banner_width = banner.width();
+        let mut overlap = glyph_width;
+        for (row, letter) in banner.rows.iter().zip(glyph) {
+            // blank columns at the end of the banner row and the start of the letter
+            let last = row.iter().rposition(|&c| c != ' ');
+            let trailing = banner_width - last.map_or(0, |i| i + 1);
+            let leading = letter
+                .iter()
+                .position(|&c| c != ' ')
+                .unwrap_or(letter.len());
+            let mut amount = trailing + leading;
+
+            // the visible characters may overlap by one more if they smush
+            if let (Some(last), Some(&right)) = (last, letter.get(leading)) {
+                if self
+                    .smush(row[last], right, banner.previous_width, glyph_width)
+                    .is_some()
+                {
+                    amount += 1;
+                }
+            }
+            overlap = overlap.min(amount);
+        }
+        overlap.min(banner_width)
+    }
+
+    // The character two overlapping sub-characters turn into, if they may overlap.
+    // `left_width` and `right_width` are the widths of the two letters.
+    fn smush(
+        &self,
+        left: char,
+        right: char,
+        left_width: usize,
+        right_width: usize,
+    ) -> Option<char> {
+        if left == ' ' {
+            return Some(right);
+        }
+        if right == ' ' {
+            return Some(left);
+        }
+        // very narrow letters are never overlapped
+        if left_width < 2 || right_width < 2 {
+            return None;
+        }
+        if self.layout & SMUSHING == 0 {
+            return None;
+        }
+
+        let hardblank = self.hardblank;
+        // no rules at all: "universal smushing", the right letter wins
+        if self.layout & HORIZONTAL_RULES == 0 {
+            if left == hardblank {
+                return Some(right);
+            }
+            return Some(if right == hardblank { left } else { right });
+        }
+
+        if self.layout & SMUSH_HARDBLANK != 0 && left == hardblank && right == hardblank {
+            return Some(hardblank);
+        }
+        if left == hardblank || right == hardblank {
+            return None;
+        }
+        if self.layout & SMUSH_EQUAL != 0 && left == right {
+            return Some(left);
+        }
+        if self.layout & SMUSH_UNDERSCORE != 0 {
+            const BORDERS: &str = "|/\\[]{}()<>";
+            if left == '_' && BORDERS.contains(right) {
+                return Some(right);
+            }
+            if right == '_' && BORDERS.contains(left) {
+                return Some(left);
+            }
+        }
+        if self.layout & SMUSH_HIERARCHY != 0 {
+            // a character from a later class replaces one from an earlier class
+            const CLASSES: [&str; 6] = ["|", "/\\", "[]", "{}", "()", "<>"];
+            let class = |c: char| CLASSES.iter().position(|class| class.contains(c));
+            if let (Some(l), Some(r)) = (class(left), class(right)) {
+                if l != r {
+                    return Some(if l > r { left } else { right });
+                }
+            }
+        }
+        if self.layout & SMUSH_PAIR != 0 {
+            let pair = [left, right];
+            if matches!(
+                pair,
+                ['[', ']'] | [']', '['] | ['{', '}'] | ['}', '{'] | ['(', ')'] | [')', '(']
+            ) {
+                return Some('|');
+            }
+        }
+        if self.layout & SMUSH_BIG_X != 0 {
+            match (left, right) {
+                ('/', '\\') => return Some('|'),
+                ('\\', '/') => return Some('Y'),
+                ('>', '<') => return Some('X'),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+// Reads the `height` lines of one letter, `None` at the end of the file
+fn read_glyph<'a, I>(
+    lines: &mut I,
+    height: usize,
+    end: usize,
+) -> Result<Option<Vec<Vec<char>>>, FontError>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    let mut glyph = Vec::with_capacity(height);
+    for row in 0..height {
+        let (number, line) = match lines.next() {
+            Some(line) => line,
+            None if row == 0 => return Ok(None),
+            None => return Err(parse_error(end, "the font ends inside a letter")),
+        };
+        let line = line.trim_end_matches(['\r', ' ']);
+        // the end mark is the last character, repeated on the last row
+        let end_mark = line
+            .chars()
+            .last()
+            .ok_or_else(|| parse_error(number, "empty letter line"))?;
+        glyph.push(
+            line.trim_end_matches(end_mark)
+                .chars()
+                .collect::<Vec<char>>(),
+        );
+    }
+
+    // all rows of a letter are as wide as the widest
+    let width = glyph.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut glyph {
+        row.resize(width, ' ');
+    }
+    Ok(Some(glyph))
+}
+
+// Codes are decimal, `0x` hexadecimal or `0` octal, negative codes are
+// valid but do not map to a character (`Ok(None)`)
+fn parse_code(code: &str) -> Option<Option<char>> {
+    let (negative, digits) = match code.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, code),
+    };
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u32::from_str_radix(hex, 16).ok()?
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        u32::from_str_radix(&digits[1..], 8).ok()?
+    } else {
+        digits.parse().ok()?
+    };
+    if negative {
+        Some(None)
+    } else {
+        Some(char::from_u32(value))
+    }
+}
+
+// A banner being built, one row of sub-characters per line of the font
+#[derive(Debug, Clone)]
+struct Banner {
+    rows: Vec<Vec<char>>,
+    // width of the last letter added, narrow letters never smush
+    previous_width: usize,
+}
+
+impl Banner {
+    fn new(height: usize) -> Banner {
+        Banner {
+            rows: vec![Vec::new(); height],
+            previous_width: 0,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.width() == 0
+    }
+}
+
+fn parse_error(line: usize, message: &str) -> FontError {
+    FontError::Parse {
+        line,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A one line high font where every letter is its own character,
+    // except for the letters given in `glyphs`
+    fn font(full_layout: i32, glyphs: &[(char, &str)]) -> Font {
+        let mut source = format!("flf2a$ 1 1 8 0 0 0 {}\n", full_layout);
+        for c in ' '..='~' {
+            let glyph = glyphs
+                .iter()
+                .find(|(g, _)| *g == c)
+                .map_or(c.to_string(), |(_, g)| g.to_string());
+            source.push_str(&format!("{}@@\n", glyph));
+        }
+        Font::parse(&source).unwrap()
+    }
+
+    fn render(font: &Font, text: &str) -> String {
+        font.render(text).join("\n")
+    }
+
+    #[test]
+    fn default_font_renders_a_banner() {
+        let banner = Font::default_font().render("Hi!");
+        assert_eq!(banner, ["    |", "|_|o|", "| ||o", ""]);
+    }
+
+    #[test]
+    fn layouts_decide_how_close_letters_get() {
+        let glyphs = [('a', "a| "), ('b', " |b")];
+        assert_eq!(render(&font(0, &glyphs), "ab"), "a|  |b");
+        assert_eq!(render(&font(KERNING as i32, &glyphs), "ab"), "a||b");
+        assert_eq!(
+            render(&font((SMUSHING | SMUSH_EQUAL) as i32, &glyphs), "ab"),
+            "a|b"
+        );
+    }
+
+    #[test]
+    fn smushing_rules() {
+        let cases = [
+            (SMUSH_EQUAL, "a|", "|b", "a|b"),
+            (SMUSH_UNDERSCORE, "a_", "/b", "a/b"),
+            (SMUSH_HIERARCHY, "a|", "{b", "a{b"),
+            (SMUSH_HIERARCHY, "a)", "/b", "a)b"),
+            (SMUSH_PAIR, "a[", "]b", "a|b"),
+            (SMUSH_BIG_X, "a/", "\\b", "a|b"),
+            (SMUSH_BIG_X, "a\\", "/b", "aYb"),
+            (SMUSH_BIG_X, "a>", "<b", "aXb"),
+        ];
+        for (rule, left, right, expected) in cases {
+            let font = font((SMUSHING | rule) as i32, &[('a', left), ('b', right)]);
+            assert_eq!(render(&font, "ab"), expected, "rule {}", rule);
+        }
+
+        // without the matching rule the letters only touch
+        let font = font((SMUSHING | SMUSH_EQUAL) as i32, &[('a', "a["), ('b', "]b")]);
+        assert_eq!(render(&font, "ab"), "a[]b");
+    }
+
+    #[test]
+    fn universal_smushing_lets_the_right_letter_win() {
+        let font = font(SMUSHING as i32, &[('a', "a/"), ('b', "\\b")]);
+        assert_eq!(render(&font, "ab"), "a\\b");
+    }
+
+    #[test]
+    fn hardblanks_keep_letters_apart() {
+        let glyphs = [('a', "a$"), ('b', "$b")];
+        assert_eq!(
+            render(&font((SMUSHING | SMUSH_EQUAL) as i32, &glyphs), "ab"),
+            "a  b"
+        );
+        assert_eq!(
+            render(&font((SMUSHING | SMUSH_HARDBLANK) as i32, &glyphs), "ab"),
+            "a b"
+        );
+    }
+
+    #[test]
+    fn narrow_letters_are_not_smushed() {
+        let font = font((SMUSHING | SMUSH_EQUAL) as i32, &[('a', "|"), ('b', "|b")]);
+        assert_eq!(render(&font, "ab"), "||b");
+    }
+
+    #[test]
+    fn wraps_between_words() {
+        let font = font(0, &[]);
+        assert_eq!(font.render_wrapped("ab cd ef", Some(5)), ["ab cd", "ef"]);
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        let error = Font::parse("not a font\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "font line 1: not a FIGlet font, missing `flf2a` signature"
+        );
+
+        let error = Font::parse("flf2a$ 2 1 8 0 0\n  @\n  @@\n").unwrap_err();
+        assert_eq!(error.to_string(), "font line 4: the font ends before `!`");
+    }
+}
@@ -0,0 +1,95 @@
+// hello-rust as a library.
+// The binary is a thin command line wrapper around this crate, other crates
+// in learning-rust use it to print their lesson headers:
+//
+//     print!("{}", hello_rust::header("Ownership"));
+//
+// Rendering never touches stdout by itself: `Renderer::render` returns a
+// `String` and `Renderer::write_to` writes into any `io::Write`.
+
+use std::io::Write;
+
+pub mod animate;
+pub mod character;
+pub mod data;
+pub mod error;
+pub mod export;
+pub mod figlet;
+pub mod render;
+pub mod style;
+mod width;
+mod wrap;
+
+pub use character::Character;
+pub use error::{Error, Result};
+pub use render::{Alignment, BorderStyle, BubbleStyle, Renderer};
+
+// What `ferris_says::say` did: Ferris says `message` into `writer`, wrapped
+// at `width` columns
+pub fn say<W: Write>(message: &str, width: Option<usize>, writer: W) -> Result<()> {
+    Renderer::new().width(width).write_to(message, writer)
+}
+
+// The renderer for lesson headers: the title in a box, nobody holding it
+pub fn header_renderer() -> Renderer {
+    Renderer::new()
+        .border(BorderStyle::Unicode)
+        .alignment(Alignment::Center)
+        .without_character()
+}
+
+// A lesson header as text, ends with `\n`
+pub fn header(title: &str) -> String {
+    header_renderer().render(title)
+}
+
+pub fn write_header<W: Write>(title: &str, writer: W) -> Result<()> {
+    header_renderer().write_to(title, writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn header_is_a_box_without_a_character() {
+        assert_eq!(
+            header("Ownership"),
+            "┌───────────┐\n│ Ownership │\n└───────────┘\n"
+        );
+
+        let mut out = Vec::new();
+        write_header("Ownership", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), header("Ownership"));
+    }
+
+    #[test]
+    fn say_writes_into_any_writer() {
+        let mut out = Vec::new();
+        say("hi", None, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            Renderer::new().render("hi")
+        );
+    }
+
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_errors_are_returned() {
+        let error = say("hi", None, Closed).unwrap_err();
+        assert!(matches!(error, Error::Io(ref e) if e.kind() == io::ErrorKind::BrokenPipe));
+        assert_eq!(error.to_string(), "closed");
+    }
+}
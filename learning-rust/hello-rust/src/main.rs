@@ -1,11 +1,117 @@
-use ferris_says::say; // from the previous step
-use std::io::{stdout, BufWriter};
+use std::io::{stdin, stdout, BufWriter, IsTerminal, Read, Write};
+use std::process;
+
+use hello_rust::animate::{self, Animation};
+use hello_rust::data;
+use hello_rust::export::{self, Format};
+use hello_rust::{style, Renderer, Result};
+
+mod cli;
+mod repl;
+
+use cli::{Input, Options};
+use repl::Session;
+
+const GREETING: &str = "Hello fellow Rustaceans!";
 
 fn main() {
-    let stdout = stdout();
-    let message = String::from("Hello fellow Rustaceans!");
-    let width = message.chars().count();
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    if options.list_characters {
+        for name in options.character_names() {
+            println!("{}", name);
+        }
+        return;
+    }
+
+    if let Err(error) = run(&options) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+fn run(options: &Options) -> Result<()> {
+    let renderer = options.renderer()?;
+
+    if options.interactive {
+        return chat(options, renderer);
+    }
 
+    let message = match &options.input {
+        Input::Args(message) => message.clone(),
+        Input::Stdin => read_stdin()?,
+        // only wait for stdin when something is actually piped in (or
+        // structured data was asked for, a greeting is no JSON)
+        Input::Default if options.format.is_some() || !stdin().is_terminal() => read_stdin()?,
+        Input::Default => String::from(GREETING),
+    };
+    let message = match options.format {
+        Some(format) => data::layout(&message, format, options.width)?,
+        None => message,
+    };
+
+    let stdout = stdout();
+    let is_terminal = stdout.is_terminal();
     let mut writer = BufWriter::new(stdout.lock());
-    say(message.as_bytes(), width, &mut writer).unwrap();
-}
\ No newline at end of file
+
+    // animations need a terminal, anywhere else only the last frame is shown
+    if options.animate && options.export.is_none() && is_terminal {
+        let theme = options.color.enabled(true).then_some(&options.theme);
+        let frames = Animation::new(&renderer, &message)
+            .motion(options.motion)
+            .timing(options.timing)
+            .frames();
+        animate::play(&frames, &mut writer, theme)?;
+        return Ok(());
+    }
+
+    let output = match options.export {
+        Some(Format::Html) => export::html(&renderer.render_lines(&message), &options.theme),
+        Some(Format::Svg) => export::svg(&renderer.render_lines(&message), &options.theme),
+        None if options.color.enabled(is_terminal) => {
+            style::paint(&renderer.render_lines(&message), Some(&options.theme))
+        }
+        None => return renderer.write_to(&message, writer),
+    };
+
+    writer.write_all(output.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn chat(options: &Options, renderer: Renderer) -> Result<()> {
+    let stdin = stdin();
+    let stdout = stdout();
+    let theme = options
+        .color
+        .enabled(stdout.is_terminal())
+        .then(|| options.theme.clone());
+    let mut session = Session::new(renderer, theme).character_dir(options.character_dir.clone());
+    if let Some(path) = options.history.clone().or_else(repl::default_history_path) {
+        session = session.history_file(path);
+    }
+
+    let prompt = stdin.is_terminal();
+    if prompt {
+        println!("Type a message, /help for commands or /quit to leave.");
+    }
+    session.run(stdin.lock(), stdout.lock(), prompt)
+}
+
+fn read_stdin() -> Result<String> {
+    let mut message = String::new();
+    stdin().read_to_string(&mut message)?;
+    // the final newline of piped input is not part of the message
+    Ok(message.trim_end_matches(['\n', '\r']).to_string())
+}
@@ -0,0 +1,507 @@
+// Our own speech bubble renderer.
+// It started as a replacement for `ferris_says::say` and can still produce
+// exactly the same bytes (see `Renderer::compat`), but it also knows about
+// alignment, padding, different borders and thought bubbles.
+
+use std::io::Write;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::character::Character;
+use crate::error::Result;
+use crate::figlet::Font;
+use crate::style::{paint, Line, Part};
+use crate::width::{display_width, pad_to};
+use crate::wrap::{max_width, wrap};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorderStyle {
+    // the classic cowsay look: `< >`, `/ \`, `| |`
+    Ascii,
+    // box drawing characters: `┌─┐`
+    Unicode,
+    // box drawing characters with rounded corners: `╭─╮`
+    Rounded,
+}
+
+// Where a FIGlet banner of the message goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BannerPosition {
+    // the banner replaces the text in the bubble
+    Inside,
+    // the bubble keeps the text, the banner goes to the right of the picture
+    Beside,
+}
+
+// Columns between the picture and a banner beside it
+const BANNER_GAP: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BubbleStyle {
+    // the character talks, the tail is a line
+    Say,
+    // the character thinks, the tail is a trail of little bubbles
+    Think,
+}
+
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    width: Option<usize>,
+    alignment: Alignment,
+    padding: usize,
+    border: BorderStyle,
+    bubble: BubbleStyle,
+    character: Character,
+    // false for a bare bubble, nobody holding it
+    with_character: bool,
+    banner: Option<(Font, BannerPosition)>,
+    compat: bool,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer {
+            width: None,
+            alignment: Alignment::Left,
+            padding: 1,
+            border: BorderStyle::Ascii,
+            bubble: BubbleStyle::Say,
+            character: Character::ferris(),
+            with_character: true,
+            banner: None,
+            compat: false,
+        }
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    // A renderer whose output is byte-for-byte what `ferris_says::say` printed.
+    // Without a width it is what `say` printed with the width of the widest line.
+    // Alignment, padding, border, bubble and character settings are ignored in this mode.
+    pub fn compat() -> Renderer {
+        Renderer {
+            compat: true,
+            ..Renderer::default()
+        }
+    }
+
+    // Wrap the text so it is at most `width` columns wide, `None` never wraps
+    pub fn width(mut self, width: Option<usize>) -> Renderer {
+        self.width = width;
+        self
+    }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Renderer {
+        self.alignment = alignment;
+        self
+    }
+
+    // Number of spaces between the border and the text on each side
+    pub fn padding(mut self, padding: usize) -> Renderer {
+        self.padding = padding;
+        self
+    }
+
+    pub fn border(mut self, border: BorderStyle) -> Renderer {
+        self.border = border;
+        self
+    }
+
+    pub fn bubble(mut self, bubble: BubbleStyle) -> Renderer {
+        self.bubble = bubble;
+        self
+    }
+
+    // Who is holding the bubble, Ferris by default
+    pub fn character(mut self, character: Character) -> Renderer {
+        self.character = character;
+        self.with_character = true;
+        self
+    }
+
+    // Only the bubble, without a character or tail under it
+    pub fn without_character(mut self) -> Renderer {
+        self.with_character = false;
+        self
+    }
+
+    // Renders the message as a banner in `font`, inside or beside the bubble
+    pub fn banner(mut self, font: Font, position: BannerPosition) -> Renderer {
+        self.banner = Some((font, position));
+        self
+    }
+
+    // Renders the bubble with the character under it, every line ends with `\n`
+    pub fn render(&self, message: &str) -> String {
+        paint(&self.render_lines(message), None)
+    }
+
+    // Writes what `render` returns into `writer` and flushes it
+    pub fn write_to<W: Write>(&self, message: &str, mut writer: W) -> Result<()> {
+        writer.write_all(self.render(message).as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    // The same picture as `render`, but as lines of spans that know which
+    // part of the picture they are, so they can be coloured or exported
+    pub fn render_lines(&self, message: &str) -> Vec<Line> {
+        self.render_frame(message, None, &self.character)
+    }
+
+    // The lines of text that go inside the bubble
+    pub(crate) fn text_lines(&self, message: &str) -> Vec<String> {
+        match &self.banner {
+            Some((font, BannerPosition::Inside)) => {
+                trim_blank_rows(font.render_wrapped(message, self.width))
+            }
+            _ => wrap(message, self.width),
+        }
+    }
+
+    pub(crate) fn current_character(&self) -> &Character {
+        &self.character
+    }
+
+    // One frame of an animation: only the first `typed` grapheme clusters of
+    // the text are shown (the bubble keeps its full size) and `character`
+    // stands in for the renderer's own character
+    pub(crate) fn render_frame(
+        &self,
+        message: &str,
+        typed: Option<usize>,
+        character: &Character,
+    ) -> Vec<Line> {
+        if self.compat {
+            return render_compat(message, self.width);
+        }
+
+        let lines = self.text_lines(message);
+        let width = max_width(&lines);
+        let lines = match typed {
+            Some(typed) => type_out(&lines, typed),
+            None => lines,
+        };
+        let frame = Frame::new(self.border, self.bubble);
+        let inner = width + 2 * self.padding;
+        let padding = " ".repeat(self.padding);
+
+        let mut out = Vec::new();
+        out.push(rule(frame.top, inner));
+        for (i, line) in lines.iter().enumerate() {
+            let (left, right) = frame.sides(i, lines.len());
+            let text = format!(
+                "{}{}{}",
+                padding,
+                align(line, width, self.alignment),
+                padding
+            );
+            out.push(bordered(left, &text, right));
+        }
+        out.push(rule(frame.bottom, inner));
+        if self.with_character {
+            out.extend(character.draw(frame.tail));
+        }
+
+        if let Some((font, BannerPosition::Beside)) = &self.banner {
+            out = beside(out, &trim_blank_rows(font.render(message)));
+        }
+        out
+    }
+}
+
+// The pieces a bubble is built from, depending on border and bubble style
+struct Frame {
+    // (left corner, horizontal line, right corner)
+    top: (&'static str, &'static str, &'static str),
+    bottom: (&'static str, &'static str, &'static str),
+    // (left, right) border of a bubble with a single line
+    only: (&'static str, &'static str),
+    // (left, right) border of the first, middle and last line of a longer bubble
+    first: (&'static str, &'static str),
+    middle: (&'static str, &'static str),
+    last: (&'static str, &'static str),
+    tail: &'static str,
+}
+
+impl Frame {
+    fn new(border: BorderStyle, bubble: BubbleStyle) -> Frame {
+        match (border, bubble) {
+            (BorderStyle::Ascii, BubbleStyle::Say) => Frame {
+                top: (" ", "_", ""),
+                bottom: (" ", "-", ""),
+                only: ("<", ">"),
+                first: ("/", "\\"),
+                middle: ("|", "|"),
+                last: ("\\", "/"),
+                tail: "\\",
+            },
+            (BorderStyle::Ascii, BubbleStyle::Think) => Frame {
+                top: (" ", "_", ""),
+                bottom: (" ", "-", ""),
+                only: ("(", ")"),
+                first: ("(", ")"),
+                middle: ("(", ")"),
+                last: ("(", ")"),
+                tail: "o",
+            },
+            (border, bubble) => {
+                let (top_left, top_right, bottom_left, bottom_right) = match border {
+                    BorderStyle::Rounded => ("╭", "╮", "╰", "╯"),
+                    _ => ("┌", "┐", "└", "┘"),
+                };
+                // thoughts get dashed lines
+                let (horizontal, vertical, tail) = match bubble {
+                    BubbleStyle::Say => ("─", "│", "╲"),
+                    BubbleStyle::Think => ("┄", "┆", "o"),
+                };
+                Frame {
+                    top: (top_left, horizontal, top_right),
+                    bottom: (bottom_left, horizontal, bottom_right),
+                    only: (vertical, vertical),
+                    first: (vertical, vertical),
+                    middle: (vertical, vertical),
+                    last: (vertical, vertical),
+                    tail,
+                }
+            }
+        }
+    }
+
+    fn sides(&self, index: usize, count: usize) -> (&'static str, &'static str) {
+        if count == 1 {
+            self.only
+        } else if index == 0 {
+            self.first
+        } else if index == count - 1 {
+            self.last
+        } else {
+            self.middle
+        }
+    }
+}
+
+fn align(line: &str, width: usize, alignment: Alignment) -> String {
+    let missing = width.saturating_sub(display_width(line));
+    let left = match alignment {
+        Alignment::Left => 0,
+        Alignment::Center => missing / 2,
+        Alignment::Right => missing,
+    };
+    pad_to(&format!("{}{}", " ".repeat(left), line), width)
+}
+
+// The first `typed` grapheme clusters of `lines`, the rest of the lines is empty
+fn type_out(lines: &[String], typed: usize) -> Vec<String> {
+    let mut left = typed;
+    lines
+        .iter()
+        .map(|line| {
+            let shown: String = line.graphemes(true).take(left).collect();
+            left -= shown.graphemes(true).count();
+            shown
+        })
+        .collect()
+}
+
+// Puts the banner to the right of the picture, top aligned
+fn beside(mut picture: Vec<Line>, banner: &[String]) -> Vec<Line> {
+    let width = picture
+        .iter()
+        .map(|line| display_width(&line.to_string()))
+        .max()
+        .unwrap_or(0);
+    while picture.len() < banner.len() {
+        picture.push(Line::new());
+    }
+    for (line, row) in picture.iter_mut().zip(banner) {
+        let missing = width - display_width(&line.to_string()) + BANNER_GAP;
+        line.push(Part::Character, &" ".repeat(missing));
+        line.push(Part::Text, row);
+    }
+    picture
+}
+
+// FIGlet letters leave empty rows for ascenders and descenders, a banner
+// does not need them at its top and bottom
+fn trim_blank_rows(mut rows: Vec<String>) -> Vec<String> {
+    while rows.last().is_some_and(|row| row.trim().is_empty()) {
+        rows.pop();
+    }
+    let blank = rows.iter().take_while(|row| row.trim().is_empty()).count();
+    rows.drain(..blank);
+    rows
+}
+
+fn rule((left, line, right): (&str, &str, &str), width: usize) -> Line {
+    Line::plain(
+        Part::Border,
+        &format!("{}{}{}", left, line.repeat(width), right),
+    )
+}
+
+fn bordered(left: &str, text: &str, right: &str) -> Line {
+    let mut line = Line::plain(Part::Border, left);
+    line.push(Part::Text, text);
+    line.push(Part::Border, right);
+    line
+}
+
+// What `ferris_says::say(message.as_bytes(), width, ..)` writes.
+// ferris_says wraps with `textwrap::fill`, which breaks lines differently from
+// our `wrap` (after hyphens, chars of long words, tabs left alone), and
+// measures lines with `UnicodeWidthStr::width` (per char, not per grapheme
+// cluster). We do both the same way so the bytes stay the same.
+fn render_compat(message: &str, width: Option<usize>) -> Vec<Line> {
+    let width =
+        width.unwrap_or_else(|| message.lines().map(|line| line.width()).max().unwrap_or(0));
+    let wrapped = textwrap::fill(message, width);
+    // an empty message has no lines at all, only the top and bottom border
+    let lines: Vec<&str> = wrapped.lines().collect();
+    let actual_width = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+
+    let mut out = Vec::new();
+    out.push(rule((" ", "_", ""), actual_width + 2));
+    for (i, line) in lines.iter().enumerate() {
+        let (left, right) = match (lines.len(), i) {
+            (1, _) => ("<", ">"),
+            (_, 0) => ("/", "\\"),
+            (count, i) if i == count - 1 => ("\\", "/"),
+            _ => ("|", "|"),
+        };
+        let fill = " ".repeat(actual_width.saturating_sub(line.width()));
+        out.push(bordered(left, &format!(" {}{} ", line, fill), right));
+    }
+    out.push(rule((" ", "-", ""), actual_width + 2));
+    out.extend(Character::ferris().draw("\\"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What the ferris_says crate itself prints
+    fn ferris_says(message: &str, width: usize) -> String {
+        let mut out = Vec::new();
+        ferris_says::say(message.as_bytes(), width, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    // The compat_*.txt files hold what ferris_says 0.2 printed for the same input
+    #[test]
+    fn compat_matches_the_golden_files() {
+        for (message, width, golden) in [
+            (
+                "Hello fellow Rustaceans!",
+                None,
+                include_str!("../tests/golden/compat_greeting.txt"),
+            ),
+            (
+                "one two three four five six",
+                Some(10),
+                include_str!("../tests/golden/compat_wrapped.txt"),
+            ),
+            (
+                "first line\nsecond\nthird line here",
+                None,
+                include_str!("../tests/golden/compat_multiline.txt"),
+            ),
+        ] {
+            assert_eq!(Renderer::compat().width(width).render(message), golden);
+            let widest = message.lines().map(|line| line.width()).max().unwrap();
+            assert_eq!(ferris_says(message, width.unwrap_or(widest)), golden);
+        }
+    }
+
+    #[test]
+    fn compat_matches_ferris_says() {
+        let messages = [
+            "",
+            "Hello fellow Rustaceans!",
+            "first line\nsecond\n\nthird line after an empty one",
+            "tabs\tin\tthe\tmiddle and\ta  tab  run",
+            "a supercalifragilisticexpialidocious word",
+            "well-known state-of-the-art hyphen-ated words",
+            "runs   of   spaces    and trailing spaces   ",
+            "   leading spaces",
+            "wide 日本語のテキスト and 😻 cats 🦀🦀🦀",
+            "combining cafe\u{301} and e\u{301}\u{302} marks",
+            "zwj family 👩‍👩‍👧 and flags 🇳🇴🇸🇪",
+            "windows\r\nline endings\r\n",
+        ];
+        for message in messages {
+            let widest = message.lines().map(|line| line.width()).max().unwrap_or(0);
+            assert_eq!(
+                Renderer::compat().render(message),
+                ferris_says(message, widest),
+                "{:?} without a width",
+                message
+            );
+            for width in 1..=30 {
+                assert_eq!(
+                    Renderer::compat().width(Some(width)).render(message),
+                    ferris_says(message, width),
+                    "{:?} at width {}",
+                    message,
+                    width
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn default_ascii_bubble_looks_like_ferris_says() {
+        for message in [
+            "Hello fellow Rustaceans!",
+            "first line\nsecond\nthird line here",
+        ] {
+            assert_eq!(
+                Renderer::new().render(message),
+                Renderer::compat().render(message)
+            );
+        }
+    }
+
+    #[test]
+    fn styled_bubbles_match_golden_files() {
+        let unicode = Renderer::new()
+            .border(BorderStyle::Unicode)
+            .alignment(Alignment::Center)
+            .width(Some(12));
+        assert_eq!(
+            unicode.render("Hello fellow Rustaceans! 😻 café"),
+            include_str!("../tests/golden/unicode_center.txt")
+        );
+
+        let rounded = Renderer::new()
+            .border(BorderStyle::Rounded)
+            .bubble(BubbleStyle::Think)
+            .alignment(Alignment::Right)
+            .padding(2)
+            .width(Some(10));
+        assert_eq!(
+            rounded.render("thinking about borrow checkers"),
+            include_str!("../tests/golden/rounded_think_right.txt")
+        );
+
+        assert_eq!(
+            Renderer::new()
+                .bubble(BubbleStyle::Think)
+                .render("one small thought"),
+            include_str!("../tests/golden/ascii_think.txt")
+        );
+    }
+}
@@ -0,0 +1,384 @@
+// The interactive mode: every line typed is said back by the character.
+// Lines starting with `/` are commands that change the rendering on the fly.
+// Everything said is kept as history, shown again with `/history` and `/show`
+// and appended to a history file so the next session can scroll back to it.
+// A history file that cannot be read or written is only worth a warning: the
+// session goes on with the history it has in memory.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use hello_rust::character::Character;
+use hello_rust::style::{paint, Theme};
+use hello_rust::{Renderer, Result};
+
+// History file used when `--history` is not given
+pub const HISTORY_ENV: &str = "HELLO_RUST_HISTORY";
+const HISTORY_FILE: &str = ".hello_rust_history";
+
+// Entries loaded from the history file. The file itself may grow to twice
+// as many before the older ones are dropped from it.
+const HISTORY_LIMIT: usize = 500;
+
+// Entries `/history` shows when not told how many
+const SCROLL_BACK: usize = 10;
+
+const PROMPT: &str = "> ";
+
+const HELP: &str = "\
+Type a message and press enter to hear it back. Commands:
+  /char <NAME>      switch to another character
+  /width <N|off>    wrap the text at N columns, or not at all
+  /theme <THEME>    change the colours
+  /history [N]      list the last N messages [default: 10]
+  /show <N>         say message N from the history again
+  /help             print this help
+  /quit             leave (so does end of input)";
+
+// `$HELLO_RUST_HISTORY`, else `.hello_rust_history` in the home directory
+pub fn default_history_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(HISTORY_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    env::var_os("HOME").map(|home| Path::new(&home).join(HISTORY_FILE))
+}
+
+pub struct Session {
+    renderer: Renderer,
+    // colours are only used when the theme should show
+    theme: Option<Theme>,
+    character_dir: Option<PathBuf>,
+    history: Vec<String>,
+    history_path: Option<PathBuf>,
+    // entries in the history file, to know when to trim it
+    history_entries: usize,
+}
+
+// What a line of input asks for
+enum Reply {
+    Print(String),
+    Quit,
+}
+
+impl Session {
+    pub fn new(renderer: Renderer, theme: Option<Theme>) -> Session {
+        Session {
+            renderer,
+            theme,
+            character_dir: None,
+            history: Vec::new(),
+            history_path: None,
+            history_entries: 0,
+        }
+    }
+
+    // Where `/char` looks for user characters
+    pub fn character_dir(mut self, dir: Option<PathBuf>) -> Session {
+        self.character_dir = dir;
+        self
+    }
+
+    // Loads earlier sessions from `path` and appends to it from now on
+    pub fn history_file(mut self, path: PathBuf) -> Session {
+        match read_history(&path) {
+            Ok(entries) => {
+                self.history_entries = entries.len();
+                self.history = entries;
+                let old = self.history.len().saturating_sub(HISTORY_LIMIT);
+                self.history.drain(..old);
+                self.history_path = Some(path);
+            }
+            Err(error) => history_failed(&path, &error),
+        }
+        self
+    }
+
+    // Reads lines from `input` until `/quit` or the end of input
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        input: R,
+        mut output: W,
+        prompt: bool,
+    ) -> Result<()> {
+        let mut lines = input.lines();
+        loop {
+            if prompt {
+                output.write_all(PROMPT.as_bytes())?;
+                output.flush()?;
+            }
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+            match self.handle(&line)? {
+                Reply::Print(text) => output.write_all(text.as_bytes())?,
+                Reply::Quit => break,
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, line: &str) -> io::Result<Reply> {
+        let line = line.trim_end_matches('\r');
+        let (command, argument) = match line.strip_prefix('/') {
+            Some(command) => command
+                .split_once(' ')
+                .map_or((command, ""), |(command, argument)| {
+                    (command, argument.trim())
+                }),
+            None if line.trim().is_empty() => return Ok(Reply::Print(String::new())),
+            None => {
+                self.remember(line);
+                return Ok(Reply::Print(self.say(line)));
+            }
+        };
+
+        let reply = match command {
+            "quit" | "exit" => return Ok(Reply::Quit),
+            "help" => format!("{}\n", HELP),
+            "char" => self.switch_character(argument),
+            "width" => self.switch_width(argument),
+            "theme" => self.switch_theme(argument),
+            "history" => self.scroll_back(argument),
+            "show" => match self.entry(argument) {
+                Ok(message) => self.say(&message),
+                Err(error) => error,
+            },
+            _ => format!("unknown command `/{}`, try /help\n", command),
+        };
+        Ok(Reply::Print(reply))
+    }
+
+    fn say(&self, message: &str) -> String {
+        paint(&self.renderer.render_lines(message), self.theme.as_ref())
+    }
+
+    fn switch_character(&mut self, name: &str) -> String {
+        if name.is_empty() {
+            let names: Vec<&str> = Character::builtin_names().collect();
+            return format!("usage: /char <NAME>, built in: {}\n", names.join(", "));
+        }
+        match Character::load(name, self.character_dir.as_deref()) {
+            Ok(character) => {
+                self.renderer = self.renderer.clone().character(character);
+                String::new()
+            }
+            Err(error) => format!("{}\n", error),
+        }
+    }
+
+    fn switch_width(&mut self, argument: &str) -> String {
+        let width = match argument {
+            "off" | "none" => None,
+            _ => match argument.parse::<usize>() {
+                Ok(width) if width > 0 => Some(width),
+                _ => return String::from("usage: /width <N|off>, N is at least 1\n"),
+            },
+        };
+        self.renderer = self.renderer.clone().width(width);
+        String::new()
+    }
+
+    fn switch_theme(&mut self, name: &str) -> String {
+        match Theme::named(name) {
+            Some(theme) => {
+                self.theme = Some(theme);
+                String::new()
+            }
+            None => {
+                let names: Vec<&str> = Theme::names().collect();
+                format!(
+                    "unknown theme `{}`, expected one of: {}\n",
+                    name,
+                    names.join(", ")
+                )
+            }
+        }
+    }
+
+    // The last entries of the history, numbered for `/show`
+    fn scroll_back(&self, argument: &str) -> String {
+        let count = if argument.is_empty() {
+            SCROLL_BACK
+        } else {
+            match argument.parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => return String::from("usage: /history [N]\n"),
+            }
+        };
+        let start = self.history.len().saturating_sub(count);
+        self.history[start..]
+            .iter()
+            .enumerate()
+            .map(|(i, message)| format!("{:>4}  {}\n", start + i + 1, message))
+            .collect()
+    }
+
+    // History entry `argument`, counted from 1 like `/history` shows them
+    fn entry(&self, argument: &str) -> std::result::Result<String, String> {
+        argument
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| self.history.get(index))
+            .cloned()
+            .ok_or_else(|| format!("no message number `{}` in the history\n", argument))
+    }
+
+    // Adds `message` to the history and the history file. When the file
+    // cannot be written the session stops using it, after a warning.
+    fn remember(&mut self, message: &str) {
+        self.history.push(message.to_string());
+        if let Some(path) = self.history_path.take() {
+            match self.append_history(&path, message) {
+                Ok(()) => self.history_path = Some(path),
+                Err(error) => history_failed(&path, &error),
+            }
+        }
+    }
+
+    fn append_history(&mut self, path: &Path, message: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", message)?;
+        self.history_entries += 1;
+        // the history in memory ends with the file's last entries
+        if self.history_entries > 2 * HISTORY_LIMIT {
+            let kept = &self.history[self.history.len() - HISTORY_LIMIT..];
+            fs::write(path, kept.join("\n") + "\n")?;
+            self.history_entries = HISTORY_LIMIT;
+        }
+        Ok(())
+    }
+}
+
+// The entries of a history file, none if there is no file yet
+fn read_history(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text.lines().map(String::from).collect()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(error),
+    }
+}
+
+fn history_failed(path: &Path, error: &io::Error) {
+    eprintln!(
+        "warning: cannot use the history file {}: {}, the history is kept for this session only",
+        path.display(),
+        error
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        Session::new(Renderer::new(), None)
+    }
+
+    fn run(session: &mut Session, input: &str) -> String {
+        let mut output = Vec::new();
+        session.run(input.as_bytes(), &mut output, false).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn lines_are_said_and_commands_change_the_rendering() {
+        let mut session = session();
+        let output = run(&mut session, "hi\n/char cow\n/width 3\nhi there\n");
+        let expected = Renderer::new().render("hi")
+            + &Renderer::new()
+                .character(Character::builtin("cow").unwrap())
+                .width(Some(3))
+                .render("hi there");
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn history_scrolls_back_and_survives_sessions() {
+        let path = env::temp_dir().join(format!("hello-rust-history-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut first = session().history_file(path.clone());
+        run(&mut first, "one\n/theme ocean\ntwo\n/quit\nnot read\n");
+
+        let mut second = session().history_file(path.clone());
+        assert_eq!(run(&mut second, "/history\n"), "   1  one\n   2  two\n");
+        assert_eq!(
+            run(&mut second, "three\n/history 1\n").lines().last(),
+            Some("   3  three")
+        );
+        assert_eq!(run(&mut second, "/show 2\n"), Renderer::new().render("two"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\nthree\n");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn an_unusable_history_file_does_not_end_the_session() {
+        // a file in a directory that is not there cannot be created
+        let missing = env::temp_dir()
+            .join(format!("hello-rust-missing-{}", std::process::id()))
+            .join("history");
+        let mut missing_dir = session().history_file(missing);
+        assert!(missing_dir.history_path.is_some());
+        let output = run(&mut missing_dir, "one\ntwo\n/history\n");
+        assert!(output.ends_with("   1  one\n   2  two\n"));
+        assert!(missing_dir.history_path.is_none());
+
+        // a directory can be neither read nor appended to as a file
+        let mut directory = session().history_file(env::temp_dir());
+        assert!(directory.history_path.is_none());
+        assert_eq!(
+            run(&mut directory, "one\n/history\n").lines().last(),
+            Some("   1  one")
+        );
+    }
+
+    #[test]
+    fn the_history_file_is_trimmed_when_appending() {
+        let path = env::temp_dir().join(format!("hello-rust-long-history-{}", std::process::id()));
+        let old: Vec<String> = (1..=2 * HISTORY_LIMIT).map(|n| n.to_string()).collect();
+        fs::write(&path, old.join("\n") + "\n").unwrap();
+
+        let mut session = session().history_file(path.clone());
+        // loading leaves the file alone
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().lines().count(),
+            2 * HISTORY_LIMIT
+        );
+        assert_eq!(session.history.len(), HISTORY_LIMIT);
+        run(&mut session, "new\n");
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), HISTORY_LIMIT);
+        assert_eq!(lines[0], (HISTORY_LIMIT + 2).to_string());
+        assert_eq!(lines.last(), Some(&"new"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mistakes_are_reported_in_the_session() {
+        let mut session = session();
+        assert_eq!(
+            run(&mut session, "/show 1\n"),
+            "no message number `1` in the history\n"
+        );
+        assert_eq!(
+            run(&mut session, "/width x\n"),
+            "usage: /width <N|off>, N is at least 1\n"
+        );
+        assert_eq!(
+            run(&mut session, "/char nobody\n"),
+            "no character named `nobody`\n"
+        );
+        assert_eq!(
+            run(&mut session, "/dance\n"),
+            "unknown command `/dance`, try /help\n"
+        );
+    }
+}
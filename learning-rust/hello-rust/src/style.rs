@@ -0,0 +1,308 @@
+// Colours and styles.
+// The renderer does not produce plain strings but lines made of spans, every
+// span knows which part of the picture it belongs to. That way the same
+// bubble can be printed as plain text, with ANSI colours or exported to HTML
+// and SVG, each output just decides how a part looks.
+
+use std::env;
+use std::fmt;
+
+// Environment variable that turns colours off, see https://no-color.org
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    // the bubble border and the tail
+    Border,
+    // the message inside the bubble
+    Text,
+    // the ASCII art character
+    Character,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub part: Part,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Line {
+    pub spans: Vec<Span>,
+}
+
+impl Line {
+    pub fn new() -> Line {
+        Line::default()
+    }
+
+    pub fn plain(part: Part, text: &str) -> Line {
+        let mut line = Line::new();
+        line.push(part, text);
+        line
+    }
+
+    // Appends text, gluing it to the last span when the part is the same
+    pub fn push(&mut self, part: Part, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.part == part => last.text.push_str(text),
+            _ => self.spans.push(Span {
+                part,
+                text: text.to_string(),
+            }),
+        }
+    }
+}
+
+// The text of the line without any styling
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for span in &self.spans {
+            f.write_str(&span.text)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // Foreground colour parameter of an ANSI SGR escape sequence
+    fn ansi(self) -> String {
+        match self {
+            Color::Black => String::from("30"),
+            Color::Red => String::from("31"),
+            Color::Green => String::from("32"),
+            Color::Yellow => String::from("33"),
+            Color::Blue => String::from("34"),
+            Color::Magenta => String::from("35"),
+            Color::Cyan => String::from("36"),
+            Color::White => String::from("37"),
+            Color::Gray => String::from("90"),
+            Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+        }
+    }
+
+    // `#rrggbb` for HTML and SVG, named colours use the xterm palette
+    pub fn hex(self) -> String {
+        let (r, g, b) = match self {
+            Color::Black => (0, 0, 0),
+            Color::Red => (205, 0, 0),
+            Color::Green => (0, 205, 0),
+            Color::Yellow => (205, 205, 0),
+            Color::Blue => (0, 0, 238),
+            Color::Magenta => (205, 0, 205),
+            Color::Cyan => (0, 205, 205),
+            Color::White => (229, 229, 229),
+            Color::Gray => (127, 127, 127),
+            Color::Rgb(r, g, b) => (r, g, b),
+        };
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    const fn new(color: Color, bold: bool) -> Style {
+        Style {
+            color: Some(color),
+            bold,
+        }
+    }
+
+    const PLAIN: Style = Style {
+        color: None,
+        bold: false,
+    };
+
+    fn is_plain(&self) -> bool {
+        self.color.is_none() && !self.bold
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub border: Style,
+    pub text: Style,
+    pub character: Style,
+}
+
+// Rust orange, as on rust-lang.org
+const RUST: Color = Color::Rgb(247, 76, 0);
+
+const THEMES: [Theme; 7] = [
+    Theme {
+        name: "ferris",
+        border: Style::new(RUST, false),
+        text: Style::new(Color::White, true),
+        character: Style::new(RUST, true),
+    },
+    Theme {
+        name: "ocean",
+        border: Style::new(Color::Blue, false),
+        text: Style::new(Color::Cyan, false),
+        character: Style::new(Color::Blue, true),
+    },
+    Theme {
+        name: "matrix",
+        border: Style::new(Color::Green, false),
+        text: Style::new(Color::Green, true),
+        character: Style::new(Color::Green, false),
+    },
+    Theme {
+        name: "sunset",
+        border: Style::new(Color::Red, false),
+        text: Style::new(Color::Yellow, true),
+        character: Style::new(Color::Magenta, false),
+    },
+    // for terminals with a light background
+    Theme {
+        name: "paper",
+        border: Style::new(Color::Black, false),
+        text: Style::new(Color::Black, true),
+        character: Style::new(Color::Blue, false),
+    },
+    Theme {
+        name: "mono",
+        border: Style::new(Color::Gray, false),
+        text: Style {
+            color: None,
+            bold: true,
+        },
+        character: Style::PLAIN,
+    },
+    Theme {
+        name: "plain",
+        border: Style::PLAIN,
+        text: Style::PLAIN,
+        character: Style::PLAIN,
+    },
+];
+
+impl Default for Theme {
+    fn default() -> Theme {
+        THEMES[0].clone()
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Theme> {
+        THEMES.iter().find(|theme| theme.name == name).cloned()
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        THEMES.iter().map(|theme| theme.name)
+    }
+
+    pub fn style(&self, part: Part) -> Style {
+        match part {
+            Part::Border => self.border,
+            Part::Text => self.text,
+            Part::Character => self.character,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    // colours when writing to a terminal and `NO_COLOR` is not set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    // Should the output be coloured, `is_terminal` tells if stdout is a TTY.
+    // An explicit `Always` wins over `NO_COLOR`, like the convention asks.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => is_terminal && !no_color(),
+        }
+    }
+}
+
+// `NO_COLOR` counts when it is present and not empty
+fn no_color() -> bool {
+    env::var_os(NO_COLOR_ENV).is_some_and(|value| !value.is_empty())
+}
+
+// The lines as text, coloured with ANSI escape sequences when `theme` is given
+pub fn paint(lines: &[Line], theme: Option<&Theme>) -> String {
+    let mut out = String::new();
+    for line in lines {
+        for span in &line.spans {
+            match theme.map(|theme| theme.style(span.part)) {
+                Some(style) if !style.is_plain() => {
+                    out.push_str(&escape(style));
+                    out.push_str(&span.text);
+                    out.push_str("\x1b[0m");
+                }
+                _ => out.push_str(&span.text),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn escape(style: Style) -> String {
+    let mut codes = Vec::new();
+    if style.bold {
+        codes.push(String::from("1"));
+    }
+    if let Some(color) = style.color {
+        codes.push(color.ansi());
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_without_theme_is_plain_text() {
+        let mut line = Line::plain(Part::Border, "< ");
+        line.push(Part::Text, "hi");
+        line.push(Part::Border, " >");
+        assert_eq!(paint(&[line.clone()], None), "< hi >\n");
+        assert_eq!(paint(&[line], Theme::named("plain").as_ref()), "< hi >\n");
+    }
+
+    #[test]
+    fn paint_wraps_spans_in_escape_sequences() {
+        let line = Line::plain(Part::Text, "hi");
+        let theme = Theme::named("matrix").unwrap();
+        assert_eq!(paint(&[line], Some(&theme)), "\x1b[1;32mhi\x1b[0m\n");
+    }
+
+    #[test]
+    fn explicit_modes_ignore_the_terminal() {
+        assert!(ColorMode::Always.enabled(false));
+        assert!(!ColorMode::Never.enabled(true));
+        assert!(!ColorMode::Auto.enabled(false));
+    }
+}
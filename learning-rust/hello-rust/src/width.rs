@@ -0,0 +1,82 @@
+// Measuring text the way a terminal draws it.
+// `str::len` counts bytes and `chars().count()` counts Unicode scalar values,
+// but neither matches the number of columns a string occupies on screen:
+// * CJK characters and most emoji take two columns (`😻`, `漢`)
+// * combining accents take none, `e\u{301}` is drawn as a single `é`
+// * some emoji are several chars glued together with zero width joiners
+// The unit a user perceives as "one character" is a grapheme cluster,
+// so that is what we measure and what we wrap on.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+// Variation selector 16 asks for the colourful (two column) emoji presentation
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+// Number of terminal columns used by `text`
+pub fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+// Columns used by a single grapheme cluster.
+// The first char decides, everything attached to it (combining marks,
+// joiners, skin tones) is drawn on top of it.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    let mut chars = grapheme.chars();
+    let base = match chars.next() {
+        Some(c) => c,
+        None => return 0,
+    };
+    let width = base.width().unwrap_or(0);
+    if width == 1 && chars.any(|c| c == EMOJI_PRESENTATION) {
+        2
+    } else {
+        width
+    }
+}
+
+// Pads `text` with spaces on the right until it is `width` columns wide
+pub fn pad_to(text: &str, width: usize) -> String {
+    let missing = width.saturating_sub(display_width(text));
+    format!("{}{}", text, " ".repeat(missing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_is_one_column_per_char() {
+        assert_eq!(display_width("Hello fellow Rustaceans!"), 24);
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(display_width("😻"), 2);
+        assert_eq!(display_width("漢字"), 4);
+        assert_eq!(display_width("cat😻cat"), 8);
+        assert_eq!(display_width("❤\u{FE0F}"), 2);
+    }
+
+    #[test]
+    fn combining_marks_take_no_columns() {
+        // 'e' followed by COMBINING ACUTE ACCENT
+        assert_eq!(display_width("cafe\u{301}"), 4);
+        assert_eq!(display_width("e\u{301}\u{301}\u{301}"), 1);
+    }
+
+    #[test]
+    fn emoji_sequences_count_once() {
+        // family: man, zero width joiner, woman, zero width joiner, girl
+        assert_eq!(display_width("👨\u{200D}👩\u{200D}👧"), 2);
+        // waving hand with a skin tone modifier
+        assert_eq!(display_width("👋🏽"), 2);
+    }
+
+    #[test]
+    fn padding_uses_display_width() {
+        assert_eq!(pad_to("😻", 4), "😻  ");
+        assert_eq!(pad_to("cafe\u{301}", 5), "cafe\u{301} ");
+        assert_eq!(pad_to("too wide", 3), "too wide");
+    }
+}
@@ -0,0 +1,164 @@
+// Splits a message into the lines that end up inside the bubble.
+// All widths are display widths (terminal columns), see `width.rs`.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::width::{display_width, grapheme_width};
+
+// Tabs would throw the bubble border off, so they are expanded to the next tab stop.
+const TAB_WIDTH: usize = 4;
+
+// Wraps every line of `text` so that none is wider than `width`.
+// Existing line breaks are kept, lines are broken at spaces where possible
+// and words longer than `width` are split. `None` disables wrapping.
+pub fn wrap(text: &str, width: Option<usize>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let line = expand_tabs(line);
+        match width {
+            Some(width) => wrap_line(&line, width, &mut lines),
+            None => lines.push(line),
+        }
+    }
+    // an empty message still gets a (small) bubble
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+// Width of the widest line, this is the width of the bubble
+pub fn max_width(lines: &[String]) -> usize {
+    lines
+        .iter()
+        .map(|line| display_width(line))
+        .max()
+        .unwrap_or(0)
+}
+
+fn wrap_line(line: &str, width: usize, lines: &mut Vec<String>) {
+    if display_width(line) <= width {
+        lines.push(line.to_string());
+        return;
+    }
+
+    let mut current = String::new();
+    let mut current_width = 0;
+    // true until the first word is placed on the current output line
+    let mut fresh = true;
+    // splitting on a single space keeps runs of spaces as empty words,
+    // so indentation inside a line survives as long as it fits
+    for (i, word) in line.split(' ').enumerate() {
+        let word_width = display_width(word);
+
+        if !fresh && current_width + 1 + word_width <= width {
+            current.push(' ');
+            current.push_str(word);
+            current_width += 1 + word_width;
+            continue;
+        }
+
+        if !fresh {
+            lines.push(current);
+            current = String::new();
+            current_width = 0;
+        }
+        // the spaces we broke at are not carried over to the next line
+        if word.is_empty() && i > 0 {
+            continue;
+        }
+        fresh = false;
+
+        // a word that does not fit on a line of its own gets chopped up,
+        // always between grapheme clusters so accents stay on their letter
+        for grapheme in word.graphemes(true) {
+            let grapheme_width = grapheme_width(grapheme);
+            if current_width > 0 && current_width + grapheme_width > width {
+                lines.push(current);
+                current = String::new();
+                current_width = 0;
+            }
+            current.push_str(grapheme);
+            current_width += grapheme_width;
+        }
+    }
+    lines.push(current);
+}
+
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for grapheme in line.graphemes(true) {
+        if grapheme == "\t" {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            expanded.push_str(grapheme);
+            column += grapheme_width(grapheme);
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::width::pad_to;
+
+    // Every line padded to the bubble width must be exactly as wide,
+    // otherwise the right border of the bubble is ragged.
+    fn assert_lines_up(lines: &[String], width: usize) {
+        let bubble = max_width(lines);
+        assert!(bubble <= width, "{:?} is wider than {}", lines, width);
+        for line in lines {
+            assert_eq!(display_width(&pad_to(line, bubble)), bubble, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn wraps_at_spaces() {
+        let lines = wrap("hello there wonderful world", Some(11));
+        assert_eq!(lines, ["hello there", "wonderful", "world"]);
+    }
+
+    #[test]
+    fn keeps_indentation_but_not_the_break() {
+        let lines = wrap("  indented words here", Some(10));
+        assert_eq!(lines, ["  indented", "words here"]);
+    }
+
+    #[test]
+    fn keeps_line_breaks() {
+        assert_eq!(wrap("one\ntwo", None), ["one", "two"]);
+        assert_eq!(wrap("", None), [""]);
+    }
+
+    #[test]
+    fn wide_characters_wrap_by_columns() {
+        // five cats are ten columns
+        let lines = wrap("😻😻😻😻😻", Some(4));
+        assert_eq!(lines, ["😻😻", "😻😻", "😻"]);
+        assert_lines_up(&lines, 4);
+    }
+
+    #[test]
+    fn combining_accents_stay_on_their_letter() {
+        let lines = wrap("e\u{301}e\u{301}e\u{301}", Some(2));
+        assert_eq!(lines, ["e\u{301}e\u{301}", "e\u{301}"]);
+    }
+
+    #[test]
+    fn mixed_width_text_lines_up() {
+        let text = "Rust 🦀 は速い and cafe\u{301} ❤\u{FE0F} 👨\u{200D}👩\u{200D}👧 漢字漢字漢字";
+        for width in 2..20 {
+            assert_lines_up(&wrap(text, Some(width)), width);
+        }
+    }
+
+    #[test]
+    fn tabs_expand_to_tab_stops() {
+        assert_eq!(wrap("a\tb", None), ["a   b"]);
+        assert_eq!(wrap("😻\tb", None), ["😻  b"]);
+    }
+}
@@ -0,0 +1,340 @@
+// All the lessons of learning-rust in one place.
+//
+//     learn list
+//     learn run shadowing
+//     learn show ownership/moves
+//     learn check --bless
+//
+// Started without a command it asks what to do, one command per line, and
+// `next` walks through the lessons in the order of the book.
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process::{self, Command};
+
+use lessons::{Course, Lesson};
+
+// In the order of the book
+const COURSES: [&Course; 3] = [
+  &variables::lessons::COURSE,
+  &branches::lessons::COURSE,
+  &ownership::lessons::COURSE,
+];
+
+const USAGE: &str = "\
+Usage: learn [COMMAND]
+
+Commands:
+  list [COURSE]               list the courses and their lessons
+  run <LESSON|COURSE>         run a lesson, or every lesson of a course
+  show <LESSON>               print the source of a lesson
+  check [LESSON|COURSE]...    run lessons and compare what they print with
+                              the expected output in `tests/golden`
+      --bless                 record the output as the expected one instead
+
+LESSON is `course/id`, or just `id` when only one course has that lesson.
+Without a command, learn reads commands from stdin and `next` runs the lesson
+after the last one.";
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  if args.is_empty() {
+    if let Err(error) = interactive() {
+      eprintln!("learn: {}", error);
+      process::exit(1);
+    }
+    return;
+  }
+  match command(&args, &mut None) {
+    Ok(true) => {}
+    Ok(false) => process::exit(1),
+    Err(message) => {
+      eprintln!("learn: {}\n\n{}", message, USAGE);
+      process::exit(2);
+    }
+  }
+}
+
+// What a name on the command line stands for
+#[derive(Clone, Copy)]
+enum Target {
+  Course(&'static Course),
+  Lesson(&'static Course, &'static Lesson),
+}
+
+impl Target {
+  fn lessons(self) -> Vec<(&'static Course, &'static Lesson)> {
+    match self {
+      Target::Course(course) => course
+        .lessons
+        .iter()
+        .map(|lesson| (course, lesson))
+        .collect(),
+      Target::Lesson(course, lesson) => vec![(course, lesson)],
+    }
+  }
+}
+
+// `courses` says whether a course name is fine too, a lesson of the same
+// name can still be had as `course/id`
+fn find(name: &str, courses: bool) -> Result<Target, String> {
+  if let Some((course_name, id)) = name.split_once('/') {
+    let course = COURSES
+      .into_iter()
+      .find(|course| course.name == course_name)
+      .ok_or_else(|| format!("there is no course `{}`", course_name))?;
+    let lesson = course
+      .lesson(id)
+      .ok_or_else(|| format!("there is no lesson `{}` in {}", id, course.name))?;
+    return Ok(Target::Lesson(course, lesson));
+  }
+  if courses {
+    if let Some(course) = COURSES.into_iter().find(|course| course.name == name) {
+      return Ok(Target::Course(course));
+    }
+  }
+  let found: Vec<(&Course, &Lesson)> = COURSES
+    .into_iter()
+    .filter_map(|course| course.lesson(name).map(|lesson| (course, lesson)))
+    .collect();
+  match found[..] {
+    [(course, lesson)] => Ok(Target::Lesson(course, lesson)),
+    [] => Err(format!("there is no lesson `{}`, see `learn list`", name)),
+    _ => {
+      let names: Vec<String> = found
+        .iter()
+        .map(|(course, lesson)| format!("{}/{}", course.name, lesson.id))
+        .collect();
+      Err(format!(
+        "`{}` is in more than one course: {}",
+        name,
+        names.join(", ")
+      ))
+    }
+  }
+}
+
+// Runs one command, `last` is the lesson `next` continues from.
+// Returns whether it went well, and an error for a command that makes no sense.
+fn command(
+  args: &[String],
+  last: &mut Option<(&'static Course, &'static Lesson)>,
+) -> Result<bool, String> {
+  let (name, rest) = args.split_first().ok_or("no command given")?;
+  match (name.as_str(), rest) {
+    ("list", []) => {
+      for course in COURSES {
+        list(course);
+      }
+    }
+    ("list", [course]) => match find(course, true)? {
+      Target::Course(course) => list(course),
+      Target::Lesson(..) => return Err(format!("`{}` is a lesson, not a course", course)),
+    },
+    ("run", [name]) => {
+      let target = find(name, true)?;
+      match target {
+        Target::Course(course) => course.run_all(),
+        Target::Lesson(course, lesson) => course.run(lesson),
+      }
+      *last = target.lessons().pop();
+    }
+    ("show", [name]) => {
+      let Target::Lesson(course, lesson) = find(name, false)? else {
+        unreachable!("only lessons are looked for");
+      };
+      match course.source_of(lesson) {
+        Some(source) => println!(
+          "// {}/{}: {}\n{}",
+          course.name, lesson.id, lesson.title, source
+        ),
+        None => println!(
+          "the source of {}/{} is not where it should be",
+          course.name, lesson.id
+        ),
+      }
+      *last = Some((course, lesson));
+    }
+    ("check", names) => {
+      let bless = names.iter().any(|name| name == "--bless");
+      let mut targets = Vec::new();
+      for name in names.iter().filter(|name| *name != "--bless") {
+        targets.extend(find(name, true)?.lessons());
+      }
+      if targets.is_empty() {
+        targets = COURSES
+          .into_iter()
+          .flat_map(|course| Target::Course(course).lessons())
+          .collect();
+      }
+      return check(&targets, bless).map_err(|error| format!("cannot check: {}", error));
+    }
+    ("help", []) => println!("{}", USAGE),
+    (other, _) => return Err(format!("unknown command or wrong arguments: `{}`", other)),
+  }
+  Ok(true)
+}
+
+fn list(course: &Course) {
+  println!("{} - {}", course.name, course.title);
+  for lesson in course.lessons {
+    println!("  {:<28} {}", lesson.id, lesson.title);
+  }
+}
+
+// Every lesson runs in a fresh `learn run` so that its output can be caught
+fn check(lessons: &[(&Course, &Lesson)], bless: bool) -> io::Result<bool> {
+  let exe = env::current_exe()?;
+  let mut failed = 0;
+  for (course, lesson) in lessons {
+    let name = format!("{}/{}", course.name, lesson.id);
+    let output = Command::new(&exe).args(["run", &name]).output()?;
+    if !output.status.success() {
+      println!("{} ... FAILED\n    it exited with {}", name, output.status);
+      failed += 1;
+      continue;
+    }
+    let actual = String::from_utf8_lossy(&output.stdout);
+
+    if bless {
+      let path = course.golden_path(lesson);
+      if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+      }
+      fs::write(&path, actual.as_bytes())?;
+      println!("{} ... recorded", name);
+      continue;
+    }
+    match course.expected(lesson)? {
+      None => {
+        println!(
+          "{} ... FAILED\n    nothing recorded yet, run `learn check --bless {}`",
+          name, name
+        );
+        failed += 1;
+      }
+      Some(expected) => match lessons::first_difference(&expected, &actual) {
+        None => println!("{} ... ok", name),
+        Some((line, want, got)) => {
+          let show = |line: Option<String>| {
+            line.map_or(String::from("nothing"), |line| format!("{:?}", line))
+          };
+          println!(
+            "{} ... FAILED\n    line {}: expected {}\n    but got {}",
+            name,
+            line,
+            show(want),
+            show(got)
+          );
+          failed += 1;
+        }
+      },
+    }
+  }
+  println!("\n{} lessons, {} failed", lessons.len(), failed);
+  Ok(failed == 0)
+}
+
+fn interactive() -> io::Result<()> {
+  println!(
+    "Type `list` to see the lessons, `next` to start with the first one and `help` for more."
+  );
+  let mut last = None;
+  let stdin = io::stdin();
+  let mut lines = stdin.lock().lines();
+  loop {
+    print!("learn> ");
+    io::stdout().flush()?;
+    let Some(line) = lines.next().transpose()? else {
+      println!();
+      return Ok(());
+    };
+    let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+    let result = match args.first().map(String::as_str) {
+      None => continue,
+      Some("quit" | "exit") => return Ok(()),
+      Some("next") => match next(last) {
+        Some((course, lesson)) => {
+          course.run(lesson);
+          last = Some((course, lesson));
+          continue;
+        }
+        None => {
+          println!("that was the last lesson");
+          continue;
+        }
+      },
+      Some(_) => command(&args, &mut last),
+    };
+    if let Err(message) = result {
+      println!("{}", message);
+    }
+  }
+}
+
+// The lesson after `last`, across courses
+fn next(last: Option<(&Course, &Lesson)>) -> Option<(&'static Course, &'static Lesson)> {
+  let mut all = COURSES
+    .into_iter()
+    .flat_map(|course| course.lessons.iter().map(move |lesson| (course, lesson)));
+  match last {
+    None => all.next(),
+    Some((course, lesson)) => all
+      .skip_while(|(c, l)| !(c.name == course.name && l.id == lesson.id))
+      .nth(1),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn name(target: Target) -> String {
+    match target {
+      Target::Course(course) => course.name.to_string(),
+      Target::Lesson(course, lesson) => format!("{}/{}", course.name, lesson.id),
+    }
+  }
+
+  #[test]
+  fn names_are_resolved() {
+    assert_eq!(
+      name(find("shadowing", true).unwrap()),
+      "variables/shadowing"
+    );
+    assert_eq!(
+      name(find("ownership/moves", false).unwrap()),
+      "ownership/moves"
+    );
+    // a course, unless only lessons are asked for
+    assert_eq!(name(find("variables", true).unwrap()), "variables");
+    assert_eq!(
+      name(find("variables", false).unwrap()),
+      "variables/variables"
+    );
+    assert!(
+      matches!(find("return_values", true), Err(message) if message.contains("more than one course"))
+    );
+    assert!(find("nothing", true).is_err());
+  }
+
+  #[test]
+  fn every_lesson_has_its_source() {
+    for course in COURSES {
+      for lesson in course.lessons {
+        let source = course.source_of(lesson).unwrap();
+        assert!(source.contains(&format!("fn {}()", lesson.id)));
+      }
+    }
+  }
+
+  #[test]
+  fn next_crosses_courses() {
+    let first = next(None).unwrap();
+    assert_eq!((first.0.name, first.1.id), ("variables", "variables"));
+    let last_of_variables = variables::lessons::COURSE.lessons.last().unwrap();
+    let after = next(Some((&variables::lessons::COURSE, last_of_variables))).unwrap();
+    assert_eq!(after.0.name, "branches");
+  }
+}
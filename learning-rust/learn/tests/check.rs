@@ -0,0 +1,18 @@
+// Every lesson still prints what is recorded in its crate's `tests/golden`.
+// After changing a lesson on purpose, `cargo run -- check --bless` records
+// the new output.
+
+use std::process::Command;
+
+#[test]
+fn lessons_print_what_they_printed_before() {
+  let output = Command::new(env!("CARGO_BIN_EXE_learn"))
+    .arg("check")
+    .output()
+    .unwrap();
+  assert!(
+    output.status.success(),
+    "{}",
+    String::from_utf8_lossy(&output.stdout)
+  );
+}
@@ -0,0 +1,258 @@
+// The lessons of the learning-rust crates, as data.
+// Every crate that teaches a chapter puts its sections in a `Course`: one
+// function per section, with an id and a title. That is enough for the
+// `learn` binary to list them, run one, show its source and check that it
+// still prints what it printed before.
+//
+//     pub const COURSE: Course = Course {
+//       name: "variables",
+//       title: "Common Programming Concepts",
+//       source: include_str!("lessons.rs"),
+//       golden: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"),
+//       prepare: || {},
+//       lessons: &[
+//         Lesson { id: "shadowing", title: "Shadowing", run: shadowing },
+//       ],
+//     };
+//
+// The id is also the name of the function, that is how `show` finds the
+// source of a lesson.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub struct Lesson {
+  pub id: &'static str,
+  pub title: &'static str,
+  // prints the lesson, without its header
+  pub run: fn(),
+}
+
+pub struct Course {
+  pub name: &'static str,
+  pub title: &'static str,
+  // the file the lesson functions are in
+  pub source: &'static str,
+  // the directory with the expected output of every lesson, `<id>.txt`
+  pub golden: &'static str,
+  // called once before lessons are run
+  pub prepare: fn(),
+  pub lessons: &'static [Lesson],
+}
+
+impl Course {
+  pub fn lesson(&self, id: &str) -> Option<&'static Lesson> {
+    self.lessons.iter().find(|lesson| lesson.id == id)
+  }
+
+  // The header and the lesson
+  pub fn run(&self, lesson: &Lesson) {
+    (self.prepare)();
+    print!("{}", hello_rust::header(lesson.title));
+    (lesson.run)();
+  }
+
+  // Every lesson in order, which is what the crate's own binary does
+  pub fn run_all(&self) {
+    (self.prepare)();
+    for lesson in self.lessons {
+      print!("{}", hello_rust::header(lesson.title));
+      (lesson.run)();
+    }
+  }
+
+  // The lesson's function as it is written in the source, with the comment
+  // lines right above it
+  pub fn source_of(&self, lesson: &Lesson) -> Option<&'static str> {
+    snippet(self.source, lesson.id)
+  }
+
+  pub fn golden_path(&self, lesson: &Lesson) -> PathBuf {
+    PathBuf::from(self.golden).join(format!("{}.txt", lesson.id))
+  }
+
+  // What the lesson is expected to print, `None` if nobody recorded it yet
+  pub fn expected(&self, lesson: &Lesson) -> io::Result<Option<String>> {
+    match fs::read_to_string(self.golden_path(lesson)) {
+      Ok(text) => Ok(Some(text)),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(error) => Err(error),
+    }
+  }
+}
+
+// Finds `fn name(` (or `fn name<`) in `source` and returns it up to its closing brace.
+// Braces in strings, chars and comments do not count.
+pub fn snippet<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+  let pattern = format!("fn {}", name);
+  let mut start = None;
+  let mut offset = 0;
+  for line in source.split_inclusive('\n') {
+    let code = line.trim_start();
+    let code = code.strip_prefix("pub ").unwrap_or(code);
+    let after = code.strip_prefix(&pattern).and_then(|rest| rest.chars().next());
+    if matches!(after, Some('(' | '<')) {
+      start = Some(offset);
+      break;
+    }
+    offset += line.len();
+  }
+  let start = start?;
+
+  // take the comment lines above along, but only when a blank line (or the
+  // start of the file) is above them: comments right under the previous
+  // item's `}` are about that item
+  let mut first = start;
+  let mut above = source[..start].split_inclusive('\n').rev().peekable();
+  while let Some(line) = above.next_if(|line| line.trim_start().starts_with("//")) {
+    first -= line.len();
+  }
+  if above.next().is_some_and(|line| !line.trim().is_empty()) {
+    first = start;
+  }
+
+  let end = start + matching_brace(&source[start..])?;
+  Some(&source[first..end])
+}
+
+// Where the first `{` in `code` is closed, just past the `}`
+fn matching_brace(code: &str) -> Option<usize> {
+  let mut depth = 0;
+  let mut chars = code.char_indices().peekable();
+  while let Some((i, c)) = chars.next() {
+    match c {
+      '{' => depth += 1,
+      '}' => {
+        depth -= 1;
+        if depth == 0 {
+          return Some(i + 1);
+        }
+      }
+      '"' => {
+        while let Some((_, c)) = chars.next() {
+          match c {
+            '\\' => {
+              chars.next();
+            }
+            '"' => break,
+            _ => {}
+          }
+        }
+      }
+      // a char literal, or a lifetime which needs no skipping
+      '\'' => {
+        let rest = &code[i + 1..];
+        let literal = if rest.starts_with('\\') {
+          // past the backslash and the char it escapes, which can be `'`
+          let mut escaped = rest.char_indices().skip(2);
+          escaped.find(|(_, c)| *c == '\'').map(|(end, _)| end + 1)
+        } else {
+          let mut rest_chars = rest.chars();
+          match (rest_chars.next(), rest_chars.next()) {
+            (Some(c), Some('\'')) => Some(c.len_utf8() + 1),
+            _ => None,
+          }
+        };
+        if let Some(length) = literal {
+          while chars.next_if(|(j, _)| *j <= i + length).is_some() {}
+        }
+      }
+      '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+        while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+      }
+      _ => {}
+    }
+  }
+  None
+}
+
+// Where `actual` first differs from `expected`: the line number (from 1) and
+// the two lines, `None` for a missing line
+pub fn first_difference(
+  expected: &str,
+  actual: &str,
+) -> Option<(usize, Option<String>, Option<String>)> {
+  let mut expected_lines = expected.lines();
+  let mut actual_lines = actual.lines();
+  for number in 1.. {
+    match (expected_lines.next(), actual_lines.next()) {
+      (None, None) => break,
+      (want, got) if want == got => continue,
+      (want, got) => return Some((number, want.map(String::from), got.map(String::from))),
+    }
+  }
+  // the same lines, but one of them ends in a newline and the other does not
+  (expected != actual).then(|| (expected.lines().count() + 1, None, None))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SOURCE: &str = r#"
+fn other() {}
+
+// Braces in strings and chars
+// do not confuse it
+pub fn tricky<'a>(s: &'a str) {
+  let open = '{';
+  let text = "}\"}";
+  // a } in a comment
+  if s.is_empty() {
+    println!("{}", text);
+  }
+}
+
+fn after() {}
+"#;
+
+  #[test]
+  fn snippets_end_at_the_matching_brace() {
+    let tricky = snippet(SOURCE, "tricky").unwrap();
+    assert!(tricky.starts_with("// Braces in strings"));
+    assert!(tricky.ends_with("  }\n}"));
+    assert_eq!(snippet(SOURCE, "other"), Some("fn other() {}"));
+    assert_eq!(snippet(SOURCE, "missing"), None);
+  }
+
+  #[test]
+  fn comments_under_the_previous_item_stay_with_it() {
+    let source = "fn one() {\n}\n// about one\nfn two() {}\n\n// about three\nfn three() {}\n";
+    assert_eq!(snippet(source, "two"), Some("fn two() {}"));
+    assert_eq!(
+      snippet(source, "three"),
+      Some("// about three\nfn three() {}")
+    );
+    let first = "// at the top\nfn first() {}\n";
+    assert_eq!(
+      snippet(first, "first"),
+      Some("// at the top\nfn first() {}")
+    );
+  }
+
+  #[test]
+  fn unfinished_char_literals_do_not_panic() {
+    assert_eq!(snippet("fn a() { '\\", "a"), None);
+    assert_eq!(snippet("fn a() { '\\é", "a"), None);
+    assert_eq!(snippet("fn a() { '\\é' }", "a"), Some("fn a() { '\\é' }"));
+    assert_eq!(
+      snippet("fn a() { let c = '\\''; let d = '}'; }", "a"),
+      Some("fn a() { let c = '\\''; let d = '}'; }")
+    );
+  }
+
+  #[test]
+  fn differences_point_at_the_line() {
+    assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+    assert_eq!(
+      first_difference("a\nb\n", "a\nc\n"),
+      Some((2, Some(String::from("b")), Some(String::from("c"))))
+    );
+    assert_eq!(
+      first_difference("a\n", "a\nb\n"),
+      Some((2, None, Some(String::from("b"))))
+    );
+    assert_eq!(first_difference("a\n", "a"), Some((2, None, None)));
+  }
+}
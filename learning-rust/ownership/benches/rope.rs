@@ -0,0 +1,67 @@
+// Editing in the middle of a multi-megabyte text, `Rope` against `String`.
+//
+//     cargo bench --bench rope
+//
+// `String::insert_str` moves everything after the insert, so it gets slower
+// as the text grows. The rope only rebuilds one path of its tree.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ownership::rope::Rope;
+
+const SIZES: [usize; 3] = [1 << 20, 4 << 20, 16 << 20];
+
+// About `size` bytes of ASCII text in lines, so chars and bytes agree
+fn text(size: usize) -> String {
+  let line = "The quick brown fox jumps over the lazy dog, again and again.\n";
+  line.repeat(size / line.len() + 1)
+}
+
+fn insert(c: &mut Criterion) {
+  let mut group = c.benchmark_group("insert in the middle");
+  for size in SIZES {
+    let mut string = text(size);
+    group.bench_function(BenchmarkId::new("String::insert_str", size), |b| {
+      b.iter(|| string.insert_str(string.len() / 2, "hello"))
+    });
+    let mut rope = Rope::from(text(size).as_str());
+    group.bench_function(BenchmarkId::new("Rope::insert", size), |b| {
+      b.iter(|| rope.insert(rope.len_chars() / 2, "hello"))
+    });
+  }
+  group.finish();
+}
+
+// An insert and a remove of the same text, so the size stays put
+fn edit(c: &mut Criterion) {
+  let mut group = c.benchmark_group("insert and remove in the middle");
+  for size in SIZES {
+    let mut string = text(size);
+    group.bench_function(BenchmarkId::new("String", size), |b| {
+      b.iter(|| {
+        let at = string.len() / 2;
+        string.insert_str(at, "hello");
+        string.drain(at..at + 5);
+      })
+    });
+    let mut rope = Rope::from(text(size).as_str());
+    group.bench_function(BenchmarkId::new("Rope", size), |b| {
+      b.iter(|| {
+        let at = rope.len_chars() / 2;
+        rope.insert(at, "hello");
+        rope.remove(at..at + 5);
+      })
+    });
+  }
+  group.finish();
+}
+
+fn lines(c: &mut Criterion) {
+  let rope = Rope::from(text(16 << 20).as_str());
+  let middle = rope.len_lines() / 2;
+  c.bench_function("Rope::line on 16 MiB", |b| {
+    b.iter(|| rope.line(middle).len_chars())
+  });
+}
+
+criterion_group!(benches, insert, edit, lines);
+criterion_main!(benches);
@@ -0,0 +1,211 @@
+// A typed arena.
+// The "Dangling reference" section fixes `fn dangle() -> &String` by giving
+// the `String` away instead. That works for one value, but graph shaped data
+// wants many values that point at each other, and none of them can own the
+// others. An arena owns them all: values go in, `&'arena T` references come
+// out, and everything is freed together when the arena is dropped.
+//
+//     let arena = Arena::new();
+//     let a = arena.alloc(1);
+//     let b = arena.alloc(2);
+//     assert_eq!(*a + *b, 3);
+//
+// The references borrow the arena, so the compiler makes sure none of them
+// outlives it, see `tests/ui` for the programs it refuses.
+//
+// Values are kept in chunks, plain `Vec`s that are never allowed to grow:
+// when a chunk is full a new one (twice as big) is started. So a value never
+// moves once it is in the arena and handing out references to it is fine,
+// even while more values are added through `&self`.
+
+use std::cell::RefCell;
+use std::cmp;
+use std::mem;
+use std::slice;
+use std::str;
+
+// The first chunk has room for this many values
+const FIRST_CHUNK: usize = 16;
+
+pub struct Arena<T> {
+  chunks: RefCell<Chunks<T>>,
+}
+
+struct Chunks<T> {
+  // where new values go, its capacity never changes
+  current: Vec<T>,
+  // the full ones, only kept to be dropped with the arena
+  full: Vec<Vec<T>>,
+}
+
+impl<T> Chunks<T> {
+  // Makes sure `current` has room for `additional` more values
+  fn reserve(&mut self, additional: usize) {
+    if self.current.capacity() - self.current.len() >= additional {
+      return;
+    }
+    let capacity = cmp::max(self.current.capacity() * 2, additional);
+    let full = mem::replace(&mut self.current, Vec::with_capacity(capacity));
+    if !full.is_empty() {
+      self.full.push(full);
+    }
+  }
+}
+
+// No `Drop` impl: the chunks drop their values themselves. That also lets the
+// values hold references into the same arena, see `values_can_point_at_each_other`.
+impl<T> Arena<T> {
+  pub fn new() -> Arena<T> {
+    Arena {
+      chunks: RefCell::new(Chunks {
+        current: Vec::with_capacity(FIRST_CHUNK),
+        full: Vec::new(),
+      }),
+    }
+  }
+
+  // Moves `value` into the arena
+  pub fn alloc(&self, value: T) -> &mut T {
+    &mut self.alloc_extend([value])[0]
+  }
+
+  // Moves all the values into the arena, next to each other.
+  // `&mut` out of `&self` is usually a mistake, here it is the whole point:
+  // every call hands out values nobody else can reach.
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc_extend(&self, values: impl IntoIterator<Item = T>) -> &mut [T] {
+    // collected first: the iterator could use the arena itself, and it
+    // cannot be borrowed twice
+    let values: Vec<T> = values.into_iter().collect();
+    let mut chunks = self.chunks.borrow_mut();
+    chunks.reserve(values.len());
+    let start = chunks.current.len();
+    chunks.current.extend(values);
+    let len = chunks.current.len() - start;
+    // The values are in the chunk for good: it never reallocates (`reserve`
+    // made room) and is only dropped with the arena, which the returned
+    // lifetime keeps alive. Each call hands out a different part of it, so
+    // the `&mut` is not shared with anybody.
+    unsafe { slice::from_raw_parts_mut(chunks.current.as_mut_ptr().add(start), len) }
+  }
+
+  // How many values the arena holds
+  pub fn len(&self) -> usize {
+    let chunks = self.chunks.borrow();
+    chunks.current.len() + chunks.full.iter().map(Vec::len).sum::<usize>()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+impl<T: Clone> Arena<T> {
+  // A copy of `values` owned by the arena
+  pub fn alloc_slice(&self, values: &[T]) -> &mut [T] {
+    self.alloc_extend(values.iter().cloned())
+  }
+}
+
+impl Arena<u8> {
+  // A copy of `text` owned by the arena
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc_str(&self, text: &str) -> &mut str {
+    let bytes = self.alloc_slice(text.as_bytes());
+    // copied from a `str`, so still valid UTF-8
+    unsafe { str::from_utf8_unchecked_mut(bytes) }
+  }
+}
+
+impl<T> Default for Arena<T> {
+  fn default() -> Arena<T> {
+    Arena::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::{Cell, RefCell};
+  use std::rc::Rc;
+
+  #[test]
+  fn values_stay_put_while_the_arena_grows() {
+    let arena = Arena::new();
+    let first = arena.alloc(String::from("first"));
+    let numbers: Vec<&mut String> = (0..1000).map(|n| arena.alloc(n.to_string())).collect();
+    first.push_str(" value");
+    assert_eq!(first, "first value");
+    assert_eq!(numbers[999], "999");
+    assert_eq!(arena.len(), 1001);
+  }
+
+  #[test]
+  fn strings_and_slices() {
+    let bytes = Arena::new();
+    let hello = bytes.alloc_str("hello");
+    let world = bytes.alloc_str("wörld");
+    hello.make_ascii_uppercase();
+    assert_eq!((&*hello, &*world), ("HELLO", "wörld"));
+
+    let arena = Arena::new();
+    let evens = arena.alloc_extend((0..10).filter(|n| n % 2 == 0));
+    let copied = arena.alloc_slice(&[7, 8, 9]);
+    assert_eq!(evens, [0, 2, 4, 6, 8]);
+    assert_eq!(copied, [7, 8, 9]);
+    // bigger than a chunk, gets a chunk of its own
+    assert_eq!(arena.alloc_extend(0..100).len(), 100);
+  }
+
+  #[test]
+  fn everything_is_dropped_with_the_arena() {
+    struct Counted(Rc<Cell<usize>>);
+    impl Drop for Counted {
+      fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+      }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let arena = Arena::new();
+    for _ in 0..100 {
+      arena.alloc(Counted(Rc::clone(&drops)));
+    }
+    assert_eq!(drops.get(), 0);
+    drop(arena);
+    assert_eq!(drops.get(), 100);
+  }
+
+  #[test]
+  fn values_can_point_at_each_other() {
+    // a cycle, which plain ownership cannot express
+    struct Node<'a> {
+      name: &'a str,
+      next: Cell<Option<&'a Node<'a>>>,
+      seen_by: RefCell<Vec<&'a str>>,
+    }
+
+    let names = Arena::new();
+    let nodes = Arena::new();
+    let make = |name| {
+      &*nodes.alloc(Node {
+        name: names.alloc_str(name),
+        next: Cell::new(None),
+        seen_by: RefCell::new(Vec::new()),
+      })
+    };
+    let a = make("a");
+    let b = make("b");
+    a.next.set(Some(b));
+    b.next.set(Some(a));
+
+    let mut node = a;
+    for _ in 0..4 {
+      let next = node.next.get().unwrap();
+      next.seen_by.borrow_mut().push(node.name);
+      node = next;
+    }
+    assert_eq!(*a.seen_by.borrow(), ["b", "b"]);
+    assert_eq!(*b.seen_by.borrow(), ["a", "a"]);
+  }
+}
@@ -0,0 +1,163 @@
+// Checks and runs programs in the toy ownership language.
+//
+//     toy [FILE]
+//
+// With a file (or `-`, or piped stdin) the whole program is checked and run.
+// Started in a terminal without arguments it reads statements one at a time,
+// see `ownership::toy` for the language.
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process;
+
+use ownership::toy::{self, Diagnostic};
+
+const USAGE: &str = "\
+Usage: toy [FILE]
+
+Checks FILE against the ownership rules and runs it. Without FILE, stdin is
+read, and in a terminal statements are checked and run as they are typed.
+
+Options:
+  -h, --help  print this help";
+
+const REPL_HELP: &str = "\
+Type statements, they are checked together with everything before them.
+A statement with an error is not kept, so you can try again.
+
+  :program  show the program so far
+  :reset    start over with an empty program
+  :help     show this help
+  :quit     leave (so does Ctrl-D)";
+
+fn main() {
+  let mut file = None;
+  for arg in env::args().skip(1) {
+    match arg.as_str() {
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        return;
+      }
+      "-" => file = Some(arg),
+      _ if arg.starts_with('-') => usage_error(&format!("unknown option `{}`", arg)),
+      _ if file.is_some() => usage_error("only one FILE can be given"),
+      _ => file = Some(arg),
+    }
+  }
+
+  let result = match file.as_deref() {
+    None if io::stdin().is_terminal() => repl(),
+    None | Some("-") => {
+      let mut source = String::new();
+      io::stdin()
+        .read_to_string(&mut source)
+        .map(|_| run_file(&source, "<stdin>"))
+    }
+    Some(name) => fs::read_to_string(name).map(|source| run_file(&source, name)),
+  };
+  match result {
+    Ok(true) => {}
+    Ok(false) => process::exit(1),
+    Err(error) => {
+      eprintln!("toy: {}", error);
+      process::exit(1);
+    }
+  }
+}
+
+fn usage_error(message: &str) -> ! {
+  eprintln!("toy: {}\n\n{}", message, USAGE);
+  process::exit(2);
+}
+
+// Returns whether the program passed the checker and ran to the end
+fn run_file(source: &str, name: &str) -> bool {
+  let stdout = io::stdout();
+  match toy::run(source, &mut stdout.lock()) {
+    Ok(()) => true,
+    Err(errors) => {
+      report(&errors, source, name);
+      false
+    }
+  }
+}
+
+fn report(errors: &[Diagnostic], source: &str, name: &str) {
+  for error in errors {
+    eprintln!("{}", error.render(source, name));
+  }
+  match errors.len() {
+    1 => eprintln!("error: aborting due to 1 previous error"),
+    n => eprintln!("error: aborting due to {} previous errors", n),
+  }
+}
+
+fn repl() -> io::Result<bool> {
+  println!("{}\n", REPL_HELP);
+  let mut program = String::new();
+  // what the program printed so far, only new output is shown
+  let mut printed = 0;
+  let mut chunk = String::new();
+
+  let stdin = io::stdin();
+  let mut lines = stdin.lock().lines();
+  loop {
+    print!("{}", if chunk.is_empty() { "toy> " } else { "...> " });
+    io::stdout().flush()?;
+    let Some(line) = lines.next().transpose()? else {
+      println!();
+      return Ok(true);
+    };
+
+    if chunk.is_empty() {
+      match line.trim() {
+        "" => continue,
+        ":quit" | ":q" => return Ok(true),
+        ":help" => {
+          println!("{}", REPL_HELP);
+          continue;
+        }
+        ":program" => {
+          print!("{}", program);
+          continue;
+        }
+        ":reset" => {
+          program.clear();
+          printed = 0;
+          continue;
+        }
+        command if command.starts_with(':') => {
+          println!("unknown command `{}`, try :help", command);
+          continue;
+        }
+        _ => {}
+      }
+    }
+    chunk.push_str(&line);
+    chunk.push('\n');
+    if !complete(&chunk) {
+      continue;
+    }
+
+    let candidate = format!("{}{}", program, chunk);
+    let mut out = Vec::new();
+    match toy::run(&candidate, &mut out) {
+      Ok(()) => {
+        io::stdout().write_all(&out[printed.min(out.len())..])?;
+        printed = out.len();
+        program = candidate;
+      }
+      Err(errors) => report(&errors, &candidate, "<repl>"),
+    }
+    chunk.clear();
+  }
+}
+
+// A chunk is run once its braces are balanced and it ends a statement
+fn complete(chunk: &str) -> bool {
+  let open = chunk.matches('{').count();
+  let close = chunk.matches('}').count();
+  let end = chunk.trim_end();
+  open <= close && (end.ends_with(';') || end.ends_with('}'))
+}
@@ -0,0 +1,106 @@
+// Word counts and text statistics for files or stdin.
+//
+//     wordstats [-n N] [-i] [FILE]...
+//
+// Without files (or with `-`) stdin is read. The numbers are for all inputs
+// together, see `ownership::stats` for how words are counted.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::process;
+
+use ownership::stats::TextStats;
+
+const USAGE: &str = "\
+Usage: wordstats [OPTIONS] [FILE]...
+
+Counts lines, words, chars and bytes of the FILEs (or stdin) and lists the
+most frequent words.
+
+Options:
+  -n, --top <N>      how many of the most frequent words to list [default: 10]
+  -i, --ignore-case  count `The` and `the` as the same word
+  -h, --help         print this help";
+
+const DEFAULT_TOP: usize = 10;
+
+fn main() {
+  let mut top = DEFAULT_TOP;
+  let mut ignore_case = false;
+  let mut files = Vec::new();
+
+  let mut args = env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        return;
+      }
+      "-i" | "--ignore-case" => ignore_case = true,
+      "-n" | "--top" => {
+        top = match args.next().map(|n| n.parse::<usize>()) {
+          Some(Ok(n)) => n,
+          _ => usage_error("`--top` expects a number"),
+        }
+      }
+      "-" => files.push(arg),
+      _ if arg.starts_with('-') => usage_error(&format!("unknown option `{}`", arg)),
+      _ => files.push(arg),
+    }
+  }
+  if files.is_empty() {
+    files.push(String::from("-"));
+  }
+
+  let mut stats = TextStats::new().ignore_case(ignore_case);
+  for name in &files {
+    let result = if name == "-" {
+      stats.read(io::stdin().lock())
+    } else {
+      File::open(name).and_then(|file| stats.read(BufReader::new(file)))
+    };
+    if let Err(error) = result {
+      eprintln!("error: {}: {}", name, error);
+      process::exit(1);
+    }
+  }
+
+  if let Err(error) = report(&stats, top) {
+    // a closed pipe (`wordstats big.txt | head`) is not worth a message
+    if error.kind() != io::ErrorKind::BrokenPipe {
+      eprintln!("error: {}", error);
+      process::exit(1);
+    }
+  }
+}
+
+fn report(stats: &TextStats, top: usize) -> io::Result<()> {
+  let stdout = io::stdout();
+  let mut out = stdout.lock();
+  writeln!(out, "lines     {:>12}", stats.lines)?;
+  writeln!(out, "words     {:>12}", stats.words)?;
+  writeln!(out, "chars     {:>12}", stats.chars)?;
+  writeln!(out, "bytes     {:>12}", stats.bytes)?;
+  writeln!(out, "distinct  {:>12}", stats.distinct())?;
+  writeln!(out, "avg word  {:>12.2}", stats.average_word_length())?;
+
+  let words = stats.top(top);
+  if !words.is_empty() {
+    writeln!(out)?;
+    let width = words
+      .iter()
+      .map(|(_, count)| count.to_string().len())
+      .max()
+      .unwrap_or(0);
+    for (word, count) in words {
+      writeln!(out, "{:>width$}  {}", count, word, width = width)?;
+    }
+  }
+  Ok(())
+}
+
+fn usage_error(message: &str) -> ! {
+  eprintln!("error: {}\n\n{}", message, USAGE);
+  process::exit(2);
+}
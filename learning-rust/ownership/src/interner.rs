@@ -0,0 +1,228 @@
+// A string interner.
+// Parsers see the same names over and over. Interning keeps one copy of each
+// distinct string and hands out a `Symbol` for it, a small `Copy` number that
+// is cheap to store, compare and hash, unlike a `String`.
+//
+//     let names = Interner::new();
+//     let a = names.intern("width");
+//     let b = names.intern(&String::from("width"));
+//     assert_eq!(a, b);
+//     assert_eq!(names.resolve(a), "width");
+//
+// The lesson's `&str` versus `String` shows up twice here. `intern` takes a
+// `&str` and copies it only the first time it is seen, `intern_owned` takes a
+// `String` and keeps its allocation. And `resolve` gives back a `&str`
+// borrowed from the interner: it stays valid while more strings are interned,
+// but it cannot outlive the interner itself.
+//
+// `Interner` is for one thread. With the `sync` feature there is also a
+// `SyncInterner` that can be shared between threads.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// A string in an interner, only meaningful for the interner that made it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+  // Symbols are numbered from 0 in the order the strings were first seen
+  pub fn index(self) -> usize {
+    self.0 as usize
+  }
+}
+
+// The part both interners share, they only differ in how they guard it
+#[derive(Default)]
+struct Strings {
+  // `&'static` is a lie the rest of this file keeps from getting out: the
+  // keys point into the strings in `strings` and are only valid as long as
+  // those are
+  symbols: HashMap<&'static str, Symbol>,
+  // A `String` never moves its text, even when the `Vec` grows and moves the
+  // `String`s themselves, and nothing is removed before the interner drops.
+  // (A `Box<str>` would do the same, but a `Box` promises to be the only
+  // pointer to its text, which the keys above break.)
+  strings: Vec<String>,
+}
+
+impl Strings {
+  fn get(&self, text: &str) -> Option<Symbol> {
+    self.symbols.get(text).copied()
+  }
+
+  fn insert(&mut self, text: String) -> Symbol {
+    let symbol = Symbol(
+      u32::try_from(self.strings.len()).expect("an interner holds at most u32::MAX strings"),
+    );
+    // the text stays where it is until the interner drops, see above
+    let key: &'static str = unsafe { &*(text.as_str() as *const str) };
+    self.strings.push(text);
+    self.symbols.insert(key, symbol);
+    symbol
+  }
+
+  // Valid until the interner drops, callers narrow it to their `&self`
+  fn resolve(&self, symbol: Symbol) -> &'static str {
+    let text = self
+      .strings
+      .get(symbol.index())
+      .expect("symbol from another interner");
+    unsafe { &*(text.as_str() as *const str) }
+  }
+}
+
+#[derive(Default)]
+pub struct Interner {
+  // `intern` takes `&self`, so looked up strings can be kept while interning
+  strings: RefCell<Strings>,
+}
+
+impl Interner {
+  pub fn new() -> Interner {
+    Interner::default()
+  }
+
+  pub fn intern(&self, text: &str) -> Symbol {
+    if let Some(symbol) = self.get(text) {
+      return symbol;
+    }
+    self.strings.borrow_mut().insert(text.to_string())
+  }
+
+  // Like `intern`, but a new string keeps the allocation it came with
+  pub fn intern_owned(&self, text: String) -> Symbol {
+    if let Some(symbol) = self.get(&text) {
+      return symbol;
+    }
+    self.strings.borrow_mut().insert(text)
+  }
+
+  // The symbol of `text` if it was interned, without interning it
+  pub fn get(&self, text: &str) -> Option<Symbol> {
+    self.strings.borrow().get(text)
+  }
+
+  // Panics for a symbol from another interner
+  pub fn resolve(&self, symbol: Symbol) -> &str {
+    self.strings.borrow().resolve(symbol)
+  }
+
+  pub fn len(&self) -> usize {
+    self.strings.borrow().strings.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+#[cfg(feature = "sync")]
+pub use self::sync::SyncInterner;
+
+#[cfg(feature = "sync")]
+mod sync {
+  use std::sync::RwLock;
+
+  use super::{Strings, Symbol};
+
+  // An `Interner` behind a lock. Lookups share a read lock, only new strings
+  // take the write lock.
+  #[derive(Default)]
+  pub struct SyncInterner {
+    strings: RwLock<Strings>,
+  }
+
+  impl SyncInterner {
+    pub fn new() -> SyncInterner {
+      SyncInterner::default()
+    }
+
+    pub fn intern(&self, text: &str) -> Symbol {
+      if let Some(symbol) = self.get(text) {
+        return symbol;
+      }
+      let mut strings = self.strings.write().unwrap();
+      // another thread may have been quicker between the two locks
+      match strings.get(text) {
+        Some(symbol) => symbol,
+        None => strings.insert(text.to_string()),
+      }
+    }
+
+    pub fn get(&self, text: &str) -> Option<Symbol> {
+      self.strings.read().unwrap().get(text)
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+      self.strings.read().unwrap().resolve(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+      self.strings.read().unwrap().strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+      self.len() == 0
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn equal_strings_get_one_symbol() {
+    let names = Interner::new();
+    let width = names.intern("width");
+    let height = names.intern_owned(String::from("height"));
+    assert_eq!(names.intern(&String::from("width")), width);
+    assert_eq!(names.intern_owned(String::from("height")), height);
+    assert_ne!(width, height);
+    assert_eq!(names.len(), 2);
+    assert_eq!(names.get("depth"), None);
+    assert_eq!((width.index(), height.index()), (0, 1));
+  }
+
+  #[test]
+  fn resolved_strings_survive_more_interning() {
+    let names = Interner::new();
+    let first = names.resolve(names.intern("first"));
+    // enough strings to make the `Vec` and the map reallocate
+    for n in 0..1000 {
+      names.intern(&n.to_string());
+    }
+    assert_eq!(first, "first");
+    assert_eq!(names.resolve(names.intern("999")), "999");
+  }
+
+  #[test]
+  fn owned_strings_keep_their_allocation() {
+    let names = Interner::new();
+    let text = String::from("kept");
+    let heap = text.as_ptr();
+    let symbol = names.intern_owned(text);
+    assert_eq!(names.resolve(symbol).as_ptr(), heap);
+  }
+
+  #[cfg(feature = "sync")]
+  #[test]
+  fn threads_share_a_sync_interner() {
+    use std::thread;
+
+    let names = SyncInterner::new();
+    let symbols: Vec<Vec<Symbol>> = thread::scope(|scope| {
+      let handles: Vec<_> = (0..4)
+        .map(|_| scope.spawn(|| (0..100).map(|n| names.intern(&n.to_string())).collect()))
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect()
+    });
+    assert_eq!(names.len(), 100);
+    assert!(symbols.iter().all(|other| *other == symbols[0]));
+    assert_eq!(names.resolve(symbols[0][42]), "42");
+  }
+}
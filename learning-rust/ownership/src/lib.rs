@@ -0,0 +1,15 @@
+// The ownership lesson grown into a small library.
+// `lessons` walks through the chapter, the other modules take its ideas further.
+
+pub mod arena;
+pub mod interner;
+pub mod lessons;
+pub mod my_string;
+pub mod pipeline;
+pub mod rope;
+pub mod shared;
+pub mod slices;
+pub mod stats;
+pub mod tokenizer;
+pub mod toy;
+pub mod trace;
@@ -0,0 +1,284 @@
+// A `String` built by hand.
+// The lesson draws a `String` as three values on the stack: a pointer to the
+// heap memory holding the text, the length in use and the capacity that was
+// allocated. `MyString` is exactly that triple, and everything `String` does
+// for us is spelled out: allocating, growing, copying on `clone` and giving
+// the memory back in `drop`.
+//
+// The unsafe parts are small and each one says why it is fine. The tests are
+// meant to run under Miri as well, which catches use after free, double free
+// and leaks:
+//
+//     cargo +nightly miri test my_string
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::str;
+
+// The first allocation holds at least this many bytes, growing from 1 to 2
+// to 4 bytes would be a waste of allocator calls
+const MIN_CAPACITY: usize = 8;
+
+pub struct MyString {
+  // the heap memory, dangling (but never read) while `cap` is 0
+  ptr: NonNull<u8>,
+  // bytes in use, always valid UTF-8
+  len: usize,
+  // bytes allocated
+  cap: usize,
+}
+
+// `MyString` owns its bytes like `String` does, nobody else can reach them
+unsafe impl Send for MyString {}
+unsafe impl Sync for MyString {}
+
+impl MyString {
+  // An empty string, nothing is allocated until text is pushed
+  pub const fn new() -> MyString {
+    MyString {
+      ptr: NonNull::dangling(),
+      len: 0,
+      cap: 0,
+    }
+  }
+
+  pub fn with_capacity(capacity: usize) -> MyString {
+    let mut s = MyString::new();
+    s.reserve(capacity);
+    s
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn capacity(&self) -> usize {
+    self.cap
+  }
+
+  // Where the text lives, handy to show that a move keeps the heap data
+  // where it is while a clone gets its own copy
+  pub fn as_ptr(&self) -> *const u8 {
+    self.ptr.as_ptr()
+  }
+
+  pub fn as_str(&self) -> &str {
+    // the first `len` bytes are initialised and valid UTF-8, see `push_str`
+    unsafe { str::from_utf8_unchecked(slice::from_raw_parts(self.ptr.as_ptr(), self.len)) }
+  }
+
+  pub fn push_str(&mut self, text: &str) {
+    self.reserve(text.len());
+    // `reserve` made room for `text` behind the `len` bytes in use, and
+    // `text` cannot overlap with them: we hold `&mut self`, it is borrowed
+    unsafe {
+      ptr::copy_nonoverlapping(text.as_ptr(), self.ptr.as_ptr().add(self.len), text.len());
+    }
+    self.len += text.len();
+  }
+
+  pub fn push(&mut self, c: char) {
+    self.push_str(c.encode_utf8(&mut [0; 4]));
+  }
+
+  // Forgets the text but keeps the memory for the next one
+  pub fn clear(&mut self) {
+    self.len = 0;
+  }
+
+  // Makes sure `additional` more bytes fit without another allocation.
+  // The capacity at least doubles, so pushing byte by byte stays cheap.
+  pub fn reserve(&mut self, additional: usize) {
+    let needed = self.len.checked_add(additional).expect("capacity overflow");
+    if needed <= self.cap {
+      return;
+    }
+    let cap = needed.max(self.cap * 2).max(MIN_CAPACITY);
+    let layout = Layout::array::<u8>(cap).expect("capacity overflow");
+
+    let ptr = if self.cap == 0 {
+      // `layout` has a size of at least `MIN_CAPACITY`, allocating zero
+      // bytes would be undefined behaviour
+      unsafe { alloc::alloc(layout) }
+    } else {
+      // `ptr` was allocated with the layout of the old capacity
+      unsafe { alloc::realloc(self.ptr.as_ptr(), self.layout(), cap) }
+    };
+    self.ptr = match NonNull::new(ptr) {
+      Some(ptr) => ptr,
+      None => alloc::handle_alloc_error(layout),
+    };
+    self.cap = cap;
+  }
+
+  fn layout(&self) -> Layout {
+    Layout::array::<u8>(self.cap).expect("the capacity was checked when allocating")
+  }
+}
+
+impl Default for MyString {
+  fn default() -> MyString {
+    MyString::new()
+  }
+}
+
+impl From<&str> for MyString {
+  fn from(text: &str) -> MyString {
+    let mut s = MyString::with_capacity(text.len());
+    s.push_str(text);
+    s
+  }
+}
+
+// A deep copy: new memory, same bytes. Only as much as the text needs is
+// allocated, the spare capacity of `self` is not copied.
+impl Clone for MyString {
+  fn clone(&self) -> MyString {
+    MyString::from(self.as_str())
+  }
+}
+
+impl Drop for MyString {
+  fn drop(&mut self) {
+    if self.cap > 0 {
+      // allocated by `reserve` with exactly this layout, and `drop` runs once
+      unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout()) }
+    }
+  }
+}
+
+// Everything `&str` can do (`len`, `chars`, slicing with `&s[1..3]`, ...)
+// works on `MyString` through this
+impl Deref for MyString {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl PartialEq for MyString {
+  fn eq(&self, other: &MyString) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+
+impl Eq for MyString {}
+
+impl PartialEq<str> for MyString {
+  fn eq(&self, other: &str) -> bool {
+    self.as_str() == other
+  }
+}
+
+impl PartialEq<&str> for MyString {
+  fn eq(&self, other: &&str) -> bool {
+    self.as_str() == *other
+  }
+}
+
+impl fmt::Display for MyString {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(self.as_str(), f)
+  }
+}
+
+impl fmt::Debug for MyString {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(self.as_str(), f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn new_strings_do_not_allocate() {
+    let s = MyString::new();
+    assert_eq!(s.capacity(), 0);
+    assert_eq!(s, "");
+    assert!(s.is_empty());
+  }
+
+  #[test]
+  fn push_grows_the_capacity_by_doubling() {
+    let mut s = MyString::new();
+    s.push_str("hello");
+    assert_eq!((s.len(), s.capacity()), (5, MIN_CAPACITY));
+    s.push_str(", world");
+    assert_eq!((s.len(), s.capacity()), (12, 16));
+    s.push('!');
+    s.push('🦀');
+    assert_eq!(s, "hello, world!🦀");
+    assert_eq!(s.capacity(), 32);
+
+    // one big push gets exactly what it needs
+    let mut big = MyString::new();
+    big.push_str(&"x".repeat(100));
+    assert_eq!(big.capacity(), 100);
+  }
+
+  #[test]
+  fn clear_keeps_the_memory() {
+    let mut s = MyString::from("hello");
+    let ptr = s.as_ptr();
+    s.clear();
+    assert_eq!(s, "");
+    s.push_str("bye");
+    assert_eq!(s, "bye");
+    assert_eq!(s.as_ptr(), ptr);
+  }
+
+  #[test]
+  fn a_move_keeps_the_heap_data_a_clone_copies_it() {
+    let s1 = MyString::from("hello");
+    let ptr = s1.as_ptr();
+
+    let s2 = s1;
+    assert_eq!(s2.as_ptr(), ptr);
+
+    let s3 = s2.clone();
+    assert_ne!(s3.as_ptr(), ptr);
+    assert_eq!(s3, s2);
+    // both are dropped here, each frees its own memory exactly once
+  }
+
+  #[test]
+  fn it_derefs_to_str() {
+    let s = MyString::from("héllo wörld");
+    assert_eq!(&s[0..1], "h");
+    assert_eq!(&s[7..], "wörld");
+    assert_eq!(s.chars().count(), 11);
+    assert_eq!(s.split(' ').collect::<Vec<_>>(), ["héllo", "wörld"]);
+    assert_eq!(crate::tokenizer::last_word(&s), Some("wörld"));
+    assert_eq!(format!("{} {:?}", s, s), "héllo wörld \"héllo wörld\"");
+  }
+
+  #[test]
+  #[should_panic]
+  fn slicing_inside_a_char_panics_like_str() {
+    let s = MyString::from("é");
+    let _ = &s[0..1];
+  }
+
+  #[test]
+  fn strings_can_move_between_threads() {
+    let s = MyString::from("sent");
+    let back = std::thread::spawn(move || {
+      let mut s = s;
+      s.push_str(" and back");
+      s
+    })
+    .join()
+    .unwrap();
+    assert_eq!(back, "sent and back");
+  }
+}
@@ -0,0 +1,356 @@
+// A text clean-up pipeline that only copies when it has to.
+// The lesson's `calculate_length(s: String)` takes the string and has to give
+// it back, `calculate_length(s: &String)` just borrows it. Text clean-up has
+// the same choice at every step: a step that returns a new `String` costs an
+// allocation even when it changed nothing, a step that returns `&str` cannot
+// return a changed text at all. `Cow<str>` ("clone on write") is either one:
+// a borrowed `&str` while nothing changed, an owned `String` once something did.
+//
+//     let mut pipeline = Pipeline::new().trim().lowercase().collapse_whitespace();
+//     assert_eq!(pipeline.run("  already clean  "), "already clean"); // borrowed
+//     assert_eq!(pipeline.run("Not  CLEAN"), "not clean"); // owned
+//
+// Every stage counts what it did, so after a run over real text the pipeline
+// can tell how many allocations a `String` in, `String` out version would
+// have made that this one did not.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::iter;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+  // whitespace off both ends
+  Trim,
+  // lower case, the way `str::to_lowercase` does it
+  Lowercase,
+  // every `from` by `to`, an empty `from` changes nothing
+  Replace { from: String, to: String },
+  // every run of whitespace to a single space
+  CollapseWhitespace,
+}
+
+// What a stage did with one text
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+  // nothing to do, the text is passed on as it came
+  Unchanged,
+  // changed without a new buffer: a borrowed text was sliced or an owned
+  // one was changed in place
+  Reused,
+  // changed into a new `String`
+  Allocated,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StageStats {
+  pub runs: usize,
+  pub unchanged: usize,
+  pub reused: usize,
+  pub allocations: usize,
+}
+
+impl StageStats {
+  // Allocations a stage that always returns a new `String` would have made on top
+  pub fn avoided(&self) -> usize {
+    self.runs - self.allocations
+  }
+
+  fn record(&mut self, outcome: Outcome) {
+    self.runs += 1;
+    match outcome {
+      Outcome::Unchanged => self.unchanged += 1,
+      Outcome::Reused => self.reused += 1,
+      Outcome::Allocated => self.allocations += 1,
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct Pipeline {
+  stages: Vec<(Stage, StageStats)>,
+  texts: usize,
+  // texts that came out still borrowing the input
+  borrowed: usize,
+}
+
+impl Pipeline {
+  pub fn new() -> Pipeline {
+    Pipeline::default()
+  }
+
+  pub fn stage(mut self, stage: Stage) -> Pipeline {
+    self.stages.push((stage, StageStats::default()));
+    self
+  }
+
+  pub fn trim(self) -> Pipeline {
+    self.stage(Stage::Trim)
+  }
+
+  pub fn lowercase(self) -> Pipeline {
+    self.stage(Stage::Lowercase)
+  }
+
+  pub fn replace(self, from: &str, to: &str) -> Pipeline {
+    self.stage(Stage::Replace {
+      from: from.to_string(),
+      to: to.to_string(),
+    })
+  }
+
+  pub fn collapse_whitespace(self) -> Pipeline {
+    self.stage(Stage::CollapseWhitespace)
+  }
+
+  // Runs every stage on `text`, a `&str` or a `String`.
+  // What comes out borrows from `text` unless some stage had to allocate.
+  pub fn run<'a>(&mut self, text: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+    let mut text = text.into();
+    self.texts += 1;
+    for (stage, stats) in &mut self.stages {
+      let (changed, outcome) = stage.apply(text);
+      stats.record(outcome);
+      text = changed;
+    }
+    if let Cow::Borrowed(_) = text {
+      self.borrowed += 1;
+    }
+    text
+  }
+
+  pub fn stats(&self) -> impl Iterator<Item = (&Stage, &StageStats)> {
+    self.stages.iter().map(|(stage, stats)| (stage, stats))
+  }
+
+  // All the stages together
+  pub fn total(&self) -> StageStats {
+    let mut total = StageStats::default();
+    for (_, stats) in &self.stages {
+      total.runs += stats.runs;
+      total.unchanged += stats.unchanged;
+      total.reused += stats.reused;
+      total.allocations += stats.allocations;
+    }
+    total
+  }
+
+  // How many texts came out without a single allocation
+  pub fn borrowed(&self) -> usize {
+    self.borrowed
+  }
+}
+
+impl Stage {
+  fn apply<'a>(&self, text: Cow<'a, str>) -> (Cow<'a, str>, Outcome) {
+    match self {
+      Stage::Trim => {
+        let end = text.trim_end().len();
+        let start = end - text[..end].trim_start().len();
+        if start == 0 && end == text.len() {
+          return (text, Outcome::Unchanged);
+        }
+        let trimmed = match text {
+          Cow::Borrowed(text) => Cow::Borrowed(&text[start..end]),
+          Cow::Owned(mut text) => {
+            text.truncate(end);
+            text.drain(..start);
+            Cow::Owned(text)
+          }
+        };
+        (trimmed, Outcome::Reused)
+      }
+      Stage::Lowercase => {
+        // titlecase letters like `ǅ` are not upper case but do change
+        if !text.chars().any(|c| c.to_lowercase().ne(iter::once(c))) {
+          return (text, Outcome::Unchanged);
+        }
+        match text {
+          Cow::Owned(mut text) if text.is_ascii() => {
+            text.make_ascii_lowercase();
+            (Cow::Owned(text), Outcome::Reused)
+          }
+          text => (Cow::Owned(text.to_lowercase()), Outcome::Allocated),
+        }
+      }
+      Stage::Replace { from, to } => {
+        if from.is_empty() || !text.contains(from.as_str()) {
+          return (text, Outcome::Unchanged);
+        }
+        match text {
+          // the same length, so nothing after a match has to move
+          Cow::Owned(mut text) if from.len() == to.len() => {
+            let mut at = 0;
+            while let Some(found) = text[at..].find(from.as_str()) {
+              let start = at + found;
+              text.replace_range(start..start + from.len(), to);
+              at = start + to.len();
+            }
+            (Cow::Owned(text), Outcome::Reused)
+          }
+          text => (
+            Cow::Owned(text.replace(from.as_str(), to)),
+            Outcome::Allocated,
+          ),
+        }
+      }
+      Stage::CollapseWhitespace => {
+        let mut previous = 'x';
+        let collapsed = text.chars().all(|c| {
+          let fine = !c.is_whitespace() || (c == ' ' && !previous.is_whitespace());
+          previous = c;
+          fine
+        });
+        if collapsed {
+          return (text, Outcome::Unchanged);
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut in_whitespace = false;
+        for c in text.chars() {
+          if !c.is_whitespace() {
+            out.push(c);
+          } else if !in_whitespace {
+            out.push(' ');
+          }
+          in_whitespace = c.is_whitespace();
+        }
+        (Cow::Owned(out), Outcome::Allocated)
+      }
+    }
+  }
+}
+
+impl fmt::Display for Stage {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Stage::Trim => write!(f, "trim"),
+      Stage::Lowercase => write!(f, "lowercase"),
+      Stage::Replace { from, to } => write!(f, "replace {:?} with {:?}", from, to),
+      Stage::CollapseWhitespace => write!(f, "collapse whitespace"),
+    }
+  }
+}
+
+// A table of what every stage did
+impl fmt::Display for Pipeline {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let width = self
+      .stages
+      .iter()
+      .map(|(stage, _)| stage.to_string().len())
+      .chain([5])
+      .max()
+      .unwrap();
+    writeln!(
+      f,
+      "{:<width$}  {:>9}  {:>9}  {:>9}  {:>11}  {:>9}",
+      "stage", "runs", "unchanged", "reused", "allocations", "avoided"
+    )?;
+    let row = |f: &mut fmt::Formatter, name: &str, stats: &StageStats| {
+      writeln!(
+        f,
+        "{:<width$}  {:>9}  {:>9}  {:>9}  {:>11}  {:>9}",
+        name,
+        stats.runs,
+        stats.unchanged,
+        stats.reused,
+        stats.allocations,
+        stats.avoided()
+      )
+    };
+    for (stage, stats) in &self.stages {
+      row(f, &stage.to_string(), stats)?;
+    }
+    row(f, "total", &self.total())?;
+    write!(
+      f,
+      "{} of {} texts came out borrowed",
+      self.borrowed, self.texts
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn clean_text_is_never_copied() {
+    let mut pipeline = Pipeline::new()
+      .trim()
+      .lowercase()
+      .replace("colour", "color")
+      .collapse_whitespace();
+    let clean = "nothing to do here";
+    let out = pipeline.run(clean);
+    assert!(matches!(out, Cow::Borrowed(_)));
+    assert_eq!(out.as_ptr(), clean.as_ptr());
+
+    // trimming a borrowed text only takes a smaller slice of it
+    let out = pipeline.run("  padded  ");
+    assert_eq!(out, "padded");
+    assert!(matches!(out, Cow::Borrowed(_)));
+    assert_eq!(pipeline.borrowed(), 2);
+    assert_eq!(pipeline.total().allocations, 0);
+    assert_eq!(pipeline.total().avoided(), 8);
+  }
+
+  #[test]
+  fn stages_change_what_they_should() {
+    let mut pipeline = Pipeline::new()
+      .trim()
+      .lowercase()
+      .replace("colour", "color")
+      .collapse_whitespace();
+    assert_eq!(
+      pipeline.run("\t The COLOUR  of\n\nÉTÉ "),
+      "the color of été"
+    );
+    assert_eq!(pipeline.run("ǅ"), "ǆ");
+    let stats: Vec<&StageStats> = pipeline.stats().map(|(_, stats)| stats).collect();
+    assert_eq!(stats[0].reused, 1);
+    assert_eq!(stats[1].allocations, 2);
+    assert_eq!(stats[2].allocations, 1);
+    assert_eq!(stats[3].allocations, 1);
+    assert_eq!(stats[3].unchanged, 1);
+  }
+
+  #[test]
+  fn owned_text_is_changed_in_place_when_it_can_be() {
+    let mut pipeline = Pipeline::new().trim().lowercase().replace("ab", "xy");
+    let text = String::from("  ABAB  ");
+    let buffer = text.as_ptr();
+    let out = pipeline.run(text);
+    assert_eq!(out, "xyxy");
+    assert_eq!(out.as_ptr(), buffer);
+    assert_eq!(pipeline.total().allocations, 0);
+    assert_eq!(pipeline.total().reused, 3);
+
+    // a longer replacement has to go somewhere new
+    let mut longer = Pipeline::new().replace("a", "aa");
+    assert_eq!(longer.run(String::from("banana")), "baanaanaa");
+    assert_eq!(longer.total().allocations, 1);
+  }
+
+  #[test]
+  fn collapses_only_real_runs() {
+    let mut pipeline = Pipeline::new().collapse_whitespace();
+    assert!(matches!(pipeline.run(" one two "), Cow::Borrowed(_)));
+    assert_eq!(pipeline.run("one\ttwo"), "one two");
+    assert_eq!(pipeline.run("one \u{3000} two  "), "one two ");
+    assert!(matches!(pipeline.run(""), Cow::Borrowed(_)));
+  }
+
+  #[test]
+  fn report_has_a_row_per_stage() {
+    let mut pipeline = Pipeline::new().trim().replace("a", "b");
+    pipeline.run(" a ");
+    pipeline.run("c");
+    let report = pipeline.to_string();
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[2].starts_with("replace \"a\" with \"b\""));
+    assert!(lines[3].starts_with("total"));
+    assert_eq!(lines[4], "1 of 2 texts came out borrowed");
+  }
+}
@@ -0,0 +1,586 @@
+// A rope: text kept as a balanced tree of small strings.
+// Inserting into the middle of a `String` moves every byte after the insert,
+// which hurts once the text is a few megabytes. A rope only rebuilds the
+// path from the root to the change, so insert, remove, slice and line lookups
+// all take O(log n).
+//
+//     let mut rope = Rope::from("hello world");
+//     rope.insert(5, ",");
+//     rope.remove(0..1);
+//     assert_eq!(rope.to_string(), "ello, world");
+//
+// It is also a worked example of ownership in a tree:
+// * every node owns its children through a `Box`, dropping the root drops the
+//   whole tree, no reference counting needed
+// * `split` and `join` take nodes by value and give back new ones. The old
+//   nodes are moved into the new tree, not copied, only the leaf that is cut
+//   in two gets new allocations
+// * to edit the tree we need the root by value, `mem::take` moves it out of
+//   `&mut self` and leaves an empty rope behind for the moment
+// * slices and chunk iterators borrow the rope, the `&str`s they hand out
+//   point straight into the leaves, and the borrow checker will not let the
+//   rope change while one of them is alive
+//
+// Positions are in chars, like in an editor, not in bytes.
+
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+// Leaves hold up to this many bytes. Small enough that cutting one is cheap,
+// big enough that the tree has few nodes.
+const MAX_LEAF: usize = 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct Rope {
+  root: Node,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+  info: Info,
+  kind: Kind,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+  Leaf(String),
+  Branch(Box<Node>, Box<Node>),
+}
+
+impl Default for Kind {
+  fn default() -> Kind {
+    Kind::Leaf(String::new())
+  }
+}
+
+// What every node knows about the text below it
+#[derive(Debug, Clone, Copy, Default)]
+struct Info {
+  bytes: usize,
+  chars: usize,
+  newlines: usize,
+  // leaves are 0, the tree is kept AVL balanced on this
+  height: usize,
+}
+
+impl Node {
+  fn leaf(text: String) -> Node {
+    let info = Info {
+      bytes: text.len(),
+      chars: text.chars().count(),
+      newlines: text.matches('\n').count(),
+      height: 0,
+    };
+    Node {
+      info,
+      kind: Kind::Leaf(text),
+    }
+  }
+
+  fn branch(left: Node, right: Node) -> Node {
+    let info = Info {
+      bytes: left.info.bytes + right.info.bytes,
+      chars: left.info.chars + right.info.chars,
+      newlines: left.info.newlines + right.info.newlines,
+      height: left.info.height.max(right.info.height) + 1,
+    };
+    Node {
+      info,
+      kind: Kind::Branch(Box::new(left), Box::new(right)),
+    }
+  }
+
+  // A balanced tree for `text`, cut into leaves at char boundaries
+  fn build(text: &str) -> Node {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.len() > MAX_LEAF {
+      let mut end = MAX_LEAF;
+      while !rest.is_char_boundary(end) {
+        end -= 1;
+      }
+      let (chunk, tail) = rest.split_at(end);
+      chunks.push(chunk);
+      rest = tail;
+    }
+    chunks.push(rest);
+    Node::from_chunks(&chunks)
+  }
+
+  fn from_chunks(chunks: &[&str]) -> Node {
+    match chunks {
+      [chunk] => Node::leaf(chunk.to_string()),
+      _ => {
+        let (left, right) = chunks.split_at(chunks.len() / 2);
+        Node::branch(Node::from_chunks(left), Node::from_chunks(right))
+      }
+    }
+  }
+
+  fn height(&self) -> usize {
+    self.info.height
+  }
+
+  // Takes a branch apart, the children move out of their boxes
+  fn into_children(self) -> (Node, Node) {
+    match self.kind {
+      Kind::Branch(left, right) => (*left, *right),
+      Kind::Leaf(_) => unreachable!("only branches are taken apart"),
+    }
+  }
+}
+
+// All the text of `left` followed by all the text of `right`.
+// If one tree is much taller, the other one is joined into its spine at the
+// same height and the nodes on the way back up are rebalanced.
+fn join(left: Node, right: Node) -> Node {
+  if left.info.chars == 0 {
+    return right;
+  }
+  if right.info.chars == 0 {
+    return left;
+  }
+  if left.height() > right.height() + 1 {
+    let (a, b) = left.into_children();
+    return balance(a, join(b, right));
+  }
+  if right.height() > left.height() + 1 {
+    let (a, b) = right.into_children();
+    return balance(join(left, a), b);
+  }
+  match (left.kind, right.kind) {
+    // two small leaves become one, so single char inserts do not leave a
+    // trail of tiny leaves behind
+    (Kind::Leaf(mut a), Kind::Leaf(b)) if a.len() + b.len() <= MAX_LEAF => {
+      a.push_str(&b);
+      Node::leaf(a)
+    }
+    (left_kind, right_kind) => Node::branch(
+      Node {
+        info: left.info,
+        kind: left_kind,
+      },
+      Node {
+        info: right.info,
+        kind: right_kind,
+      },
+    ),
+  }
+}
+
+// A branch over `left` and `right` whose heights differ by at most 2,
+// rotated back into AVL shape
+fn balance(left: Node, right: Node) -> Node {
+  if left.height() > right.height() + 1 {
+    let (a, b) = left.into_children();
+    if a.height() >= b.height() {
+      Node::branch(a, Node::branch(b, right))
+    } else {
+      let (ba, bb) = b.into_children();
+      Node::branch(Node::branch(a, ba), Node::branch(bb, right))
+    }
+  } else if right.height() > left.height() + 1 {
+    let (a, b) = right.into_children();
+    if b.height() >= a.height() {
+      Node::branch(Node::branch(left, a), b)
+    } else {
+      let (aa, ab) = a.into_children();
+      Node::branch(Node::branch(left, aa), Node::branch(ab, b))
+    }
+  } else {
+    Node::branch(left, right)
+  }
+}
+
+// The first `at` chars and the rest, as two trees
+fn split(node: Node, at: usize) -> (Node, Node) {
+  if at == 0 {
+    return (Node::default(), node);
+  }
+  if at == node.info.chars {
+    return (node, Node::default());
+  }
+  match node.kind {
+    Kind::Leaf(mut text) => {
+      let tail = text.split_off(byte_offset(&text, at));
+      (Node::leaf(text), Node::leaf(tail))
+    }
+    Kind::Branch(left, right) => {
+      if at <= left.info.chars {
+        let (a, b) = split(*left, at);
+        (a, join(b, *right))
+      } else {
+        let (a, b) = split(*right, at - left.info.chars);
+        (join(*left, a), b)
+      }
+    }
+  }
+}
+
+fn byte_offset(text: &str, chars: usize) -> usize {
+  text
+    .char_indices()
+    .nth(chars)
+    .map_or(text.len(), |(i, _)| i)
+}
+
+impl Rope {
+  pub fn new() -> Rope {
+    Rope::default()
+  }
+
+  pub fn len_bytes(&self) -> usize {
+    self.root.info.bytes
+  }
+
+  pub fn len_chars(&self) -> usize {
+    self.root.info.chars
+  }
+
+  // Like `str::lines` plus one: a text ending in `\n` has an empty last line
+  pub fn len_lines(&self) -> usize {
+    self.root.info.newlines + 1
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.root.info.chars == 0
+  }
+
+  // Inserts `text` before the char at `at`, panics if `at > len_chars()`
+  pub fn insert(&mut self, at: usize, text: &str) {
+    assert!(
+      at <= self.len_chars(),
+      "char index {} is out of bounds of a rope of {} chars",
+      at,
+      self.len_chars()
+    );
+    if text.is_empty() {
+      return;
+    }
+    let (left, right) = split(mem::take(&mut self.root), at);
+    self.root = join(join(left, Node::build(text)), right);
+  }
+
+  // Removes the chars in `range`
+  pub fn remove(&mut self, range: Range<usize>) {
+    self.check_range(&range);
+    let (rest, tail) = split(mem::take(&mut self.root), range.end);
+    let (head, _removed) = split(rest, range.start);
+    self.root = join(head, tail);
+  }
+
+  // The chars in `range`, borrowed from the rope
+  pub fn slice(&self, range: Range<usize>) -> RopeSlice<'_> {
+    self.check_range(&range);
+    RopeSlice {
+      root: &self.root,
+      start: range.start,
+      end: range.end,
+    }
+  }
+
+  // All of the text, as chunks
+  pub fn chunks(&self) -> Chunks<'_> {
+    self.slice(0..self.len_chars()).chunks()
+  }
+
+  pub fn char(&self, at: usize) -> char {
+    assert!(at < self.len_chars(), "char index {} is out of bounds", at);
+    let mut node = &self.root;
+    let mut at = at;
+    loop {
+      match &node.kind {
+        Kind::Leaf(text) => return text.chars().nth(at).expect("checked above"),
+        Kind::Branch(left, right) => {
+          if at < left.info.chars {
+            node = left;
+          } else {
+            at -= left.info.chars;
+            node = right;
+          }
+        }
+      }
+    }
+  }
+
+  // Line `n` counted from 0, with its `\n`
+  pub fn line(&self, n: usize) -> RopeSlice<'_> {
+    assert!(n < self.len_lines(), "line {} is out of bounds", n);
+    self.slice(self.line_to_char(n)..self.line_to_char(n + 1))
+  }
+
+  // Where line `n` starts, `len_lines()` gives the end of the text
+  pub fn line_to_char(&self, n: usize) -> usize {
+    assert!(n <= self.len_lines(), "line {} is out of bounds", n);
+    if n == self.len_lines() {
+      return self.len_chars();
+    }
+    let mut node = &self.root;
+    let (mut n, mut chars) = (n, 0);
+    loop {
+      match &node.kind {
+        Kind::Leaf(text) => {
+          // just after the `n`th newline in the leaf
+          let start = match n {
+            0 => 0,
+            n => text
+              .chars()
+              .enumerate()
+              .filter(|(_, c)| *c == '\n')
+              .nth(n - 1)
+              .map_or(0, |(i, _)| i + 1),
+          };
+          return chars + start;
+        }
+        Kind::Branch(left, right) => {
+          if n <= left.info.newlines {
+            node = left;
+          } else {
+            n -= left.info.newlines;
+            chars += left.info.chars;
+            node = right;
+          }
+        }
+      }
+    }
+  }
+
+  // The line the char at `at` is on
+  pub fn char_to_line(&self, at: usize) -> usize {
+    assert!(at <= self.len_chars(), "char index {} is out of bounds", at);
+    let mut node = &self.root;
+    let (mut at, mut lines) = (at, 0);
+    loop {
+      match &node.kind {
+        Kind::Leaf(text) => return lines + text.chars().take(at).filter(|c| *c == '\n').count(),
+        Kind::Branch(left, right) => {
+          if at <= left.info.chars {
+            node = left;
+          } else {
+            at -= left.info.chars;
+            lines += left.info.newlines;
+            node = right;
+          }
+        }
+      }
+    }
+  }
+
+  fn check_range(&self, range: &Range<usize>) {
+    assert!(
+      range.start <= range.end && range.end <= self.len_chars(),
+      "char range {:?} is out of bounds of a rope of {} chars",
+      range,
+      self.len_chars()
+    );
+  }
+}
+
+impl From<&str> for Rope {
+  fn from(text: &str) -> Rope {
+    Rope {
+      root: Node::build(text),
+    }
+  }
+}
+
+impl fmt::Display for Rope {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.chunks().try_for_each(|chunk| f.write_str(chunk))
+  }
+}
+
+// A char range of a rope. Making one is O(1), it is just the bounds.
+#[derive(Debug, Clone, Copy)]
+pub struct RopeSlice<'a> {
+  root: &'a Node,
+  start: usize,
+  end: usize,
+}
+
+impl<'a> RopeSlice<'a> {
+  pub fn len_chars(&self) -> usize {
+    self.end - self.start
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.start == self.end
+  }
+
+  pub fn chunks(&self) -> Chunks<'a> {
+    Chunks {
+      stack: vec![self.root],
+      skip: self.start,
+      remaining: self.len_chars(),
+    }
+  }
+
+  pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+    self.chunks().flat_map(str::chars)
+  }
+}
+
+impl fmt::Display for RopeSlice<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.chunks().try_for_each(|chunk| f.write_str(chunk))
+  }
+}
+
+// The text of a rope or a slice, leaf by leaf. The `&str`s are borrowed from
+// the leaves, nothing is copied.
+pub struct Chunks<'a> {
+  // nodes still to visit, the next one on top
+  stack: Vec<&'a Node>,
+  // chars to skip before the slice starts
+  skip: usize,
+  // chars left to hand out
+  remaining: usize,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+  type Item = &'a str;
+
+  fn next(&mut self) -> Option<&'a str> {
+    while self.remaining > 0 {
+      let node = self.stack.pop()?;
+      match &node.kind {
+        Kind::Branch(left, right) => {
+          self.stack.push(right);
+          // a subtree entirely before the start is not visited at all
+          if self.skip >= left.info.chars {
+            self.skip -= left.info.chars;
+          } else {
+            self.stack.push(left);
+          }
+        }
+        Kind::Leaf(text) => {
+          if self.skip >= node.info.chars {
+            self.skip -= node.info.chars;
+            continue;
+          }
+          let rest = &text[byte_offset(text, self.skip)..];
+          let taken = (node.info.chars - self.skip).min(self.remaining);
+          self.remaining -= taken;
+          self.skip = 0;
+          return Some(&rest[..byte_offset(rest, taken)]);
+        }
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Every branch is AVL balanced and knows the right totals
+  fn check_tree(node: &Node) {
+    if let Kind::Branch(left, right) = &node.kind {
+      check_tree(left);
+      check_tree(right);
+      assert!(left.height().abs_diff(right.height()) <= 1);
+      assert_eq!(node.info.chars, left.info.chars + right.info.chars);
+      assert_eq!(node.info.height, left.height().max(right.height()) + 1);
+    }
+  }
+
+  #[test]
+  fn edits_in_the_middle() {
+    let mut rope = Rope::from("hello world");
+    rope.insert(5, ",");
+    rope.insert(12, "!");
+    assert_eq!(rope.to_string(), "hello, world!");
+    rope.remove(0..7);
+    assert_eq!(rope.to_string(), "world!");
+    assert_eq!(rope.slice(1..4).to_string(), "orl");
+    assert_eq!(rope.char(5), '!');
+
+    let mut empty = Rope::new();
+    empty.insert(0, "");
+    assert!(empty.is_empty());
+    assert_eq!(empty.len_lines(), 1);
+  }
+
+  #[test]
+  fn positions_are_chars() {
+    let mut rope = Rope::from("grüße, 世界");
+    rope.insert(4, "ss");
+    assert_eq!(rope.to_string(), "grüßsse, 世界");
+    assert_eq!(rope.len_chars(), 11);
+    assert_eq!(rope.len_bytes(), "grüßsse, 世界".len());
+    assert_eq!(rope.slice(9..11).to_string(), "世界");
+  }
+
+  #[test]
+  fn lines() {
+    let rope = Rope::from("one\ntwo\n\nfour\n");
+    assert_eq!(rope.len_lines(), 5);
+    let lines: Vec<String> = (0..rope.len_lines())
+      .map(|n| rope.line(n).to_string())
+      .collect();
+    assert_eq!(lines, ["one\n", "two\n", "\n", "four\n", ""]);
+    assert_eq!(rope.line_to_char(2), 8);
+    assert_eq!(rope.char_to_line(0), 0);
+    assert_eq!(rope.char_to_line(4), 1);
+    assert_eq!(rope.char_to_line(rope.len_chars()), 4);
+  }
+
+  #[test]
+  fn chunks_borrow_the_leaves() {
+    let text = "abcdefghij".repeat(1000);
+    let rope = Rope::from(text.as_str());
+    check_tree(&rope.root);
+    assert!(rope.chunks().count() >= 10);
+    assert!(rope.chunks().all(|chunk| chunk.len() <= MAX_LEAF));
+    let slice = rope.slice(995..3005);
+    assert_eq!(slice.chunks().collect::<String>(), text[995..3005]);
+    assert_eq!(slice.chars().count(), 2010);
+  }
+
+  #[test]
+  fn matches_a_string_under_random_edits() {
+    // a small linear congruential generator, enough for picking positions
+    let mut seed: u64 = 42;
+    let mut next = move |bound: usize| {
+      seed = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+      (seed >> 33) as usize % bound.max(1)
+    };
+    let words = [
+      "a",
+      "é",
+      "line\n",
+      "",
+      "ünïcödé ",
+      "\n\n",
+      &"x".repeat(3000),
+    ];
+
+    let mut rope = Rope::new();
+    let mut model: Vec<char> = Vec::new();
+    for _ in 0..2000 {
+      if next(3) == 0 && !model.is_empty() {
+        let start = next(model.len());
+        let end = start + next((model.len() - start).min(50) + 1);
+        rope.remove(start..end);
+        model.drain(start..end);
+      } else {
+        let at = next(model.len() + 1);
+        let word = words[next(words.len())];
+        rope.insert(at, word);
+        model.splice(at..at, word.chars());
+      }
+    }
+    check_tree(&rope.root);
+    let expected: String = model.iter().collect();
+    assert_eq!(rope.to_string(), expected);
+    assert_eq!(rope.len_lines(), expected.split('\n').count());
+    for line in 0..rope.len_lines() {
+      let start = rope.line_to_char(line);
+      assert_eq!(rope.char_to_line(start), line);
+    }
+  }
+}
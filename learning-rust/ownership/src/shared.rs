@@ -0,0 +1,280 @@
+// Shared ownership, for when one owner is not enough.
+// Every value in the lessons has exactly one owner. A document does not fit
+// that: a section belongs to its chapter, but it also wants to know which
+// chapter it is in, and other sections link to it. `Rc<T>` lets many owners
+// share a value (it is dropped with the last of them), `Weak<T>` points at it
+// without owning it, and `RefCell<T>` lets a value behind a shared `Rc` still
+// be changed, checking the borrow rules while the program runs instead.
+//
+//     let book = Node::new("The Book");
+//     let chapter = Node::new("Ownership");
+//     book.add_child(Rc::clone(&chapter));
+//     assert_eq!(chapter.path(), "The Book / Ownership");
+//
+// Children are owned (`Rc`), the parent is only known (`Weak`), so the tree
+// goes away with its root. Links can be either: a strong link keeps its
+// target alive, and two strong links that point at each other keep each other
+// alive forever. `find_cycle` looks for such cycles and `LeakCheck` reports
+// what is still alive when a scope ends. `sync` has the same document for
+// threads, with `Arc` and `Mutex`.
+
+mod leak;
+pub mod sync;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::{Rc, Weak};
+
+pub use leak::{Counts, LeakCheck, Report, Watch};
+
+#[derive(Debug)]
+pub struct Node {
+  pub title: String,
+  body: RefCell<String>,
+  children: RefCell<Vec<Rc<Node>>>,
+  parent: RefCell<Weak<Node>>,
+  links: RefCell<Vec<Link>>,
+}
+
+#[derive(Debug)]
+enum Link {
+  Strong(Rc<Node>),
+  Weak(Weak<Node>),
+}
+
+impl Node {
+  pub fn new(title: &str) -> Rc<Node> {
+    Rc::new(Node {
+      title: title.to_string(),
+      body: RefCell::new(String::new()),
+      children: RefCell::new(Vec::new()),
+      parent: RefCell::new(Weak::new()),
+      links: RefCell::new(Vec::new()),
+    })
+  }
+
+  // `self: &Rc<Node>` because the child keeps a `Weak` to this very `Rc`
+  pub fn add_child(self: &Rc<Node>, child: Rc<Node>) {
+    *child.parent.borrow_mut() = Rc::downgrade(self);
+    self.children.borrow_mut().push(child);
+  }
+
+  // `None` for the root, and for a node whose parent is gone
+  pub fn parent(&self) -> Option<Rc<Node>> {
+    self.parent.borrow().upgrade()
+  }
+
+  // Clones of the `Rc`s, which only counts them up
+  pub fn children(&self) -> Vec<Rc<Node>> {
+    self.children.borrow().clone()
+  }
+
+  // A link that keeps `target` alive
+  pub fn link_to(&self, target: &Rc<Node>) {
+    self
+      .links
+      .borrow_mut()
+      .push(Link::Strong(Rc::clone(target)));
+  }
+
+  // A link that does not, it is skipped once `target` is gone
+  pub fn weak_link_to(&self, target: &Rc<Node>) {
+    self
+      .links
+      .borrow_mut()
+      .push(Link::Weak(Rc::downgrade(target)));
+  }
+
+  // The nodes linked to that are still there
+  pub fn links(&self) -> Vec<Rc<Node>> {
+    self
+      .links
+      .borrow()
+      .iter()
+      .filter_map(|link| match link {
+        Link::Strong(target) => Some(Rc::clone(target)),
+        Link::Weak(target) => target.upgrade(),
+      })
+      .collect()
+  }
+
+  // Drops the links to `target`, strong or weak
+  pub fn unlink(&self, target: &Rc<Node>) {
+    self.links.borrow_mut().retain(|link| match link {
+      Link::Strong(other) => !Rc::ptr_eq(other, target),
+      Link::Weak(other) => !Weak::ptr_eq(other, &Rc::downgrade(target)),
+    });
+  }
+
+  // The body can be written through a shared `Rc`, `RefCell` makes sure
+  // nobody reads it at the same time
+  pub fn append(&self, text: &str) {
+    self.body.borrow_mut().push_str(text);
+  }
+
+  pub fn body(&self) -> String {
+    self.body.borrow().clone()
+  }
+
+  // The titles from the root down to this node
+  pub fn path(&self) -> String {
+    let mut titles = vec![self.title.clone()];
+    let mut parent = self.parent();
+    while let Some(node) = parent {
+      titles.push(node.title.clone());
+      parent = node.parent();
+    }
+    titles.reverse();
+    titles.join(" / ")
+  }
+
+  // What this node keeps alive: its children and strong links
+  fn owned(&self) -> Vec<Rc<Node>> {
+    let mut owned = self.children();
+    for link in self.links.borrow().iter() {
+      if let Link::Strong(target) = link {
+        owned.push(Rc::clone(target));
+      }
+    }
+    owned
+  }
+}
+
+// A cycle of owning pointers reachable from `root`, from a node back to
+// itself, `None` if there is none. Weak pointers never make a cycle: they
+// do not keep anything alive.
+pub fn find_cycle(root: &Rc<Node>) -> Option<Vec<Rc<Node>>> {
+  find_cycle_by(
+    Rc::clone(root),
+    |node| Rc::as_ptr(node) as usize,
+    |node| node.owned(),
+  )
+}
+
+// Depth first search, for `Rc` and `Arc` graphs alike. `key` tells nodes
+// apart (their address), `edges` lists the nodes one owns.
+fn find_cycle_by<P: Clone>(
+  root: P,
+  key: impl Fn(&P) -> usize,
+  edges: impl Fn(&P) -> Vec<P>,
+) -> Option<Vec<P>> {
+  // nodes whose every path was followed already
+  let mut done = HashSet::new();
+  // the path from the root, with what is left to visit from each node
+  let mut path: Vec<(P, Vec<P>)> = vec![(root.clone(), edges(&root))];
+  while let Some((_, next)) = path.last_mut() {
+    let Some(node) = next.pop() else {
+      let (node, _) = path.pop().unwrap();
+      done.insert(key(&node));
+      continue;
+    };
+    if done.contains(&key(&node)) {
+      continue;
+    }
+    if let Some(start) = path
+      .iter()
+      .position(|(on_path, _)| key(on_path) == key(&node))
+    {
+      let mut cycle: Vec<P> = path[start..].iter().map(|(node, _)| node.clone()).collect();
+      cycle.push(node);
+      return Some(cycle);
+    }
+    let next = edges(&node);
+    path.push((node, next));
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn titles(nodes: &[Rc<Node>]) -> Vec<&str> {
+    nodes.iter().map(|node| node.title.as_str()).collect()
+  }
+
+  #[test]
+  fn a_tree_knows_its_parents_without_owning_them() {
+    let book = Node::new("The Book");
+    let chapter = Node::new("Ownership");
+    let section = Node::new("References");
+    book.add_child(Rc::clone(&chapter));
+    chapter.add_child(Rc::clone(&section));
+    section.append("At any given time, ");
+    section.append("one mutable reference.");
+
+    assert_eq!(section.path(), "The Book / Ownership / References");
+    assert_eq!(section.body(), "At any given time, one mutable reference.");
+    // `chapter` and the book's child
+    assert_eq!(Rc::strong_count(&chapter), 2);
+    // the section's parent pointer
+    assert_eq!(Rc::weak_count(&chapter), 1);
+    assert!(find_cycle(&book).is_none());
+
+    drop(book);
+    assert!(chapter.parent().is_none());
+    assert_eq!(chapter.path(), "Ownership");
+  }
+
+  #[test]
+  fn strong_links_can_make_a_cycle() {
+    let book = Node::new("The Book");
+    let moves = Node::new("Moves");
+    let clones = Node::new("Clones");
+    book.add_child(Rc::clone(&moves));
+    book.add_child(Rc::clone(&clones));
+    moves.link_to(&clones);
+    assert!(find_cycle(&book).is_none());
+
+    clones.link_to(&moves);
+    let cycle = find_cycle(&book).unwrap();
+    assert_eq!(titles(&cycle), ["Clones", "Moves", "Clones"]);
+
+    // a weak link back is fine
+    clones.unlink(&moves);
+    clones.weak_link_to(&moves);
+    assert!(find_cycle(&book).is_none());
+    assert_eq!(titles(&clones.links()), ["Moves"]);
+  }
+
+  #[test]
+  fn a_child_that_owns_its_ancestor() {
+    let book = Node::new("The Book");
+    let chapter = Node::new("Ownership");
+    book.add_child(Rc::clone(&chapter));
+    chapter.add_child(Rc::clone(&book));
+    assert_eq!(
+      titles(&find_cycle(&book).unwrap()),
+      ["The Book", "Ownership", "The Book"]
+    );
+    // broken by hand, or neither would ever be dropped
+    chapter.children.borrow_mut().clear();
+  }
+
+  // leaks `a` and `b` on purpose, Miri would fail the run for it
+  #[test]
+  #[cfg_attr(miri, ignore)]
+  fn the_leak_check_finds_the_cycle() {
+    let report = Rc::new(RefCell::new(None));
+    let seen = Rc::clone(&report);
+    {
+      let mut check =
+        LeakCheck::new("cycle").on_exit(move |report| *seen.borrow_mut() = Some(report.clone()));
+      let a = Node::new("a");
+      let b = Node::new("b");
+      let c = Node::new("c");
+      check.watch("a", &a);
+      check.watch("b", &b);
+      check.watch("c", &c);
+      // leaked for real, the test cannot get them back either
+      a.link_to(&b);
+      b.link_to(&a);
+      c.weak_link_to(&a);
+    }
+    let report = report.borrow_mut().take().unwrap();
+    let leaked: Vec<&str> = report.leaked().map(|(name, _)| name).collect();
+    assert_eq!(leaked, ["a", "b"]);
+    assert_eq!(report.counts("a"), Some(Counts { strong: 1, weak: 0 }));
+    assert_eq!(report.counts("c"), Some(Counts { strong: 0, weak: 0 }));
+  }
+}
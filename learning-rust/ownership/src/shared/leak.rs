@@ -0,0 +1,156 @@
+// What is still alive when a scope ends.
+// Create the check first in the scope, so that it is dropped last, and
+// `watch` the `Rc`s and `Arc`s of interest. Once everything else in the scope
+// is gone, whatever still has a strong count is kept alive from somewhere
+// else: handed out of the scope, or leaked by a cycle.
+//
+//     let mut check = LeakCheck::new("graph");
+//     let a = Node::new("a");
+//     check.watch("a", &a);
+//
+// The check only holds `Weak`s, which keep nothing alive, and leaves its own
+// out of the weak counts it reports.
+
+use std::fmt;
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Counts {
+  pub strong: usize,
+  pub weak: usize,
+}
+
+// `Rc` and `Arc`, anything the check can keep a `Weak` to
+pub trait Watch {
+  fn watcher(&self) -> Box<dyn Fn() -> Counts>;
+}
+
+impl<T: ?Sized + 'static> Watch for Rc<T> {
+  fn watcher(&self) -> Box<dyn Fn() -> Counts> {
+    let weak: rc::Weak<T> = Rc::downgrade(self);
+    Box::new(move || Counts {
+      strong: weak.strong_count(),
+      // without the check's own, and 0 anyway once the value is gone
+      weak: weak.weak_count().saturating_sub(1),
+    })
+  }
+}
+
+impl<T: ?Sized + 'static> Watch for Arc<T> {
+  fn watcher(&self) -> Box<dyn Fn() -> Counts> {
+    let weak: sync::Weak<T> = Arc::downgrade(self);
+    Box::new(move || Counts {
+      strong: weak.strong_count(),
+      weak: weak.weak_count().saturating_sub(1),
+    })
+  }
+}
+
+type Watcher = Box<dyn Fn() -> Counts>;
+type OnExit = Box<dyn FnOnce(&Report)>;
+
+pub struct LeakCheck {
+  name: String,
+  watched: Vec<(String, Watcher)>,
+  on_exit: Option<OnExit>,
+}
+
+impl LeakCheck {
+  // Without `on_exit` the report goes to stderr, if anything leaked
+  pub fn new(name: &str) -> LeakCheck {
+    LeakCheck {
+      name: name.to_string(),
+      watched: Vec::new(),
+      on_exit: None,
+    }
+  }
+
+  // What to do with the report when the scope ends
+  pub fn on_exit(mut self, on_exit: impl FnOnce(&Report) + 'static) -> LeakCheck {
+    self.on_exit = Some(Box::new(on_exit));
+    self
+  }
+
+  pub fn watch(&mut self, name: &str, value: &impl Watch) {
+    self.watched.push((name.to_string(), value.watcher()));
+  }
+
+  // The counts as they are now
+  pub fn report(&self) -> Report {
+    Report {
+      name: self.name.clone(),
+      entries: self
+        .watched
+        .iter()
+        .map(|(name, counts)| (name.clone(), counts()))
+        .collect(),
+    }
+  }
+}
+
+impl Drop for LeakCheck {
+  fn drop(&mut self) {
+    let report = self.report();
+    match self.on_exit.take() {
+      Some(on_exit) => on_exit(&report),
+      None if report.leaked().next().is_some() => eprintln!("{}", report),
+      None => {}
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+  pub name: String,
+  pub entries: Vec<(String, Counts)>,
+}
+
+impl Report {
+  // The watched values that are still alive
+  pub fn leaked(&self) -> impl Iterator<Item = (&str, Counts)> {
+    self
+      .entries
+      .iter()
+      .filter(|(_, counts)| counts.strong > 0)
+      .map(|(name, counts)| (name.as_str(), *counts))
+  }
+
+  pub fn counts(&self, name: &str) -> Option<Counts> {
+    self
+      .entries
+      .iter()
+      .find(|(watched, _)| watched == name)
+      .map(|(_, counts)| *counts)
+  }
+}
+
+impl fmt::Display for Report {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "leak check `{}`: {} of {} still alive",
+      self.name,
+      self.leaked().count(),
+      self.entries.len()
+    )?;
+    let width = self
+      .entries
+      .iter()
+      .map(|(name, _)| name.len())
+      .max()
+      .unwrap_or(0);
+    for (name, counts) in &self.entries {
+      if counts.strong == 0 {
+        write!(f, "\n  {:<width$}  dropped", name)?;
+      } else {
+        write!(
+          f,
+          "\n  {:<width$}  strong {}, weak {}",
+          name, counts.strong, counts.weak
+        )?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,163 @@
+// The document again, for threads.
+// `Rc` counts its owners with plain integers, so two threads cloning the same
+// `Rc` could lose a count: the compiler refuses to send one to another thread.
+// `Arc` counts atomically and may be shared. `RefCell` has the same problem
+// with its borrow flag, here a `Mutex` makes threads take turns instead.
+//
+//     let doc = Node::new("Notes");
+//     let writer = Arc::clone(&doc);
+//     thread::spawn(move || writer.append("from a thread")).join().unwrap();
+//
+// A lock is held only inside the methods, never while another node is
+// locked, so two threads working on linked nodes cannot deadlock.
+
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+#[derive(Debug)]
+pub struct Node {
+  pub title: String,
+  body: Mutex<String>,
+  children: Mutex<Vec<Arc<Node>>>,
+  parent: Mutex<Weak<Node>>,
+  links: Mutex<Vec<Arc<Node>>>,
+}
+
+// A thread that panicked while holding the lock leaves a poisoned mutex.
+// The data in here is only ever appended to, so it is still fine to use.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+  mutex
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Node {
+  pub fn new(title: &str) -> Arc<Node> {
+    Arc::new(Node {
+      title: title.to_string(),
+      body: Mutex::new(String::new()),
+      children: Mutex::new(Vec::new()),
+      parent: Mutex::new(Weak::new()),
+      links: Mutex::new(Vec::new()),
+    })
+  }
+
+  pub fn add_child(self: &Arc<Node>, child: Arc<Node>) {
+    *lock(&child.parent) = Arc::downgrade(self);
+    lock(&self.children).push(child);
+  }
+
+  pub fn parent(&self) -> Option<Arc<Node>> {
+    lock(&self.parent).upgrade()
+  }
+
+  pub fn children(&self) -> Vec<Arc<Node>> {
+    lock(&self.children).clone()
+  }
+
+  // A link that keeps `target` alive, the only kind that can make a cycle
+  pub fn link_to(&self, target: &Arc<Node>) {
+    lock(&self.links).push(Arc::clone(target));
+  }
+
+  pub fn unlink(&self, target: &Arc<Node>) {
+    lock(&self.links).retain(|other| !Arc::ptr_eq(other, target));
+  }
+
+  pub fn append(&self, text: &str) {
+    lock(&self.body).push_str(text);
+  }
+
+  pub fn body(&self) -> String {
+    lock(&self.body).clone()
+  }
+
+  pub fn path(&self) -> String {
+    let mut titles = vec![self.title.clone()];
+    let mut parent = self.parent();
+    while let Some(node) = parent {
+      titles.push(node.title.clone());
+      parent = node.parent();
+    }
+    titles.reverse();
+    titles.join(" / ")
+  }
+
+  fn owned(&self) -> Vec<Arc<Node>> {
+    let mut owned = self.children();
+    owned.extend(lock(&self.links).iter().cloned());
+    owned
+  }
+}
+
+// Like `shared::find_cycle`. The graph can change while it is searched, the
+// answer is about some moment during the search.
+pub fn find_cycle(root: &Arc<Node>) -> Option<Vec<Arc<Node>>> {
+  super::find_cycle_by(
+    Arc::clone(root),
+    |node| Arc::as_ptr(node) as usize,
+    |node| node.owned(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shared::LeakCheck;
+  use std::thread;
+
+  #[test]
+  fn threads_share_the_document() {
+    let book = Node::new("The Book");
+    let handles: Vec<_> = (0..4)
+      .map(|n| {
+        let book = Arc::clone(&book);
+        thread::spawn(move || {
+          let chapter = Node::new(&format!("Chapter {}", n));
+          book.add_child(Arc::clone(&chapter));
+          for _ in 0..100 {
+            chapter.append(".");
+            book.append("+");
+          }
+          chapter.path()
+        })
+      })
+      .collect();
+    for handle in handles {
+      assert!(handle.join().unwrap().starts_with("The Book / Chapter "));
+    }
+    assert_eq!(book.body().len(), 400);
+    assert_eq!(book.children().len(), 4);
+    assert!(book
+      .children()
+      .iter()
+      .all(|chapter| chapter.body().len() == 100));
+    // every thread's clone is gone again
+    assert_eq!(Arc::strong_count(&book), 1);
+  }
+
+  #[test]
+  fn cycles_across_threads() {
+    let mut check = LeakCheck::new("threads");
+    let a = Node::new("a");
+    let b = Node::new("b");
+    check.watch("a", &a);
+    check.watch("b", &b);
+    a.add_child(Arc::clone(&b));
+    let linker = {
+      let (a, b) = (Arc::clone(&a), Arc::clone(&b));
+      thread::spawn(move || b.link_to(&a))
+    };
+    linker.join().unwrap();
+    // `a` itself and the link from `b`
+    assert_eq!(check.report().counts("a").unwrap().strong, 2);
+
+    let cycle = find_cycle(&a).unwrap();
+    let titles: Vec<&str> = cycle.iter().map(|node| node.title.as_str()).collect();
+    assert_eq!(titles, ["a", "b", "a"]);
+
+    b.unlink(&a);
+    assert!(find_cycle(&a).is_none());
+    drop((cycle, a, b));
+    assert_eq!(check.report().leaked().count(), 0);
+  }
+}
@@ -0,0 +1,362 @@
+// Slice utilities, the grown up version of `&a[1..3]`.
+// The lesson's last example borrows two numbers out of an array without
+// copying them. Everything here works the same way on any `[T]`: the results
+// are subslices of the input (or the input itself, rearranged in place), so
+// nothing is allocated and the compiler keeps the input alive for as long as
+// a result is held on to.
+//
+//     let a = [1, 2, 2, 2, 3, 5, 8];
+//     assert_eq!(equal_range(&a, &2), &[2, 2, 2]);
+//     assert_eq!(chunk_by(&a, |x, y| x == y).count(), 5);
+//
+// `std` has some of these too (`windows`, `chunk_by`, `partition_point`,
+// `rotate_left`), they are written out here to show how little they need.
+
+use std::cmp::Ordering;
+use std::mem;
+
+// Windows of `size` elements, starting every `step` elements.
+// A window that would run past the end is left out, like `slice::windows`.
+pub fn windows<T>(slice: &[T], size: usize, step: usize) -> Windows<'_, T> {
+  assert!(size > 0 && step > 0, "window size and step must not be 0");
+  Windows { slice, size, step }
+}
+
+#[derive(Debug, Clone)]
+pub struct Windows<'a, T> {
+  // from the start of the next window to the end
+  slice: &'a [T],
+  size: usize,
+  step: usize,
+}
+
+impl<'a, T> Iterator for Windows<'a, T> {
+  type Item = &'a [T];
+
+  fn next(&mut self) -> Option<&'a [T]> {
+    if self.slice.len() < self.size {
+      return None;
+    }
+    let window = &self.slice[..self.size];
+    self.slice = self.slice.get(self.step..).unwrap_or(&[]);
+    Some(window)
+  }
+}
+
+// Runs of elements where `same(previous, next)` holds for every neighbour
+pub fn chunk_by<T, F>(slice: &[T], same: F) -> ChunkBy<'_, T, F>
+where
+  F: FnMut(&T, &T) -> bool,
+{
+  ChunkBy { slice, same }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkBy<'a, T, F> {
+  slice: &'a [T],
+  same: F,
+}
+
+impl<'a, T, F> Iterator for ChunkBy<'a, T, F>
+where
+  F: FnMut(&T, &T) -> bool,
+{
+  type Item = &'a [T];
+
+  fn next(&mut self) -> Option<&'a [T]> {
+    if self.slice.is_empty() {
+      return None;
+    }
+    let mut end = 1;
+    while end < self.slice.len() && (self.same)(&self.slice[end - 1], &self.slice[end]) {
+      end += 1;
+    }
+    let (chunk, rest) = self.slice.split_at(end);
+    self.slice = rest;
+    Some(chunk)
+  }
+}
+
+// The index of the first element for which `is_before` is false, when
+// `slice` has all the elements it holds for first
+pub fn partition_point<T>(slice: &[T], mut is_before: impl FnMut(&T) -> bool) -> usize {
+  // the answer is always in low..=high
+  let (mut low, mut high) = (0, slice.len());
+  while low < high {
+    let middle = low + (high - low) / 2;
+    if is_before(&slice[middle]) {
+      low = middle + 1;
+    } else {
+      high = middle;
+    }
+  }
+  low
+}
+
+// Where the first element not less than `x` is in a sorted slice
+pub fn lower_bound<T: Ord>(slice: &[T], x: &T) -> usize {
+  lower_bound_by(slice, |element| element.cmp(x))
+}
+
+// Where the first element greater than `x` is in a sorted slice
+pub fn upper_bound<T: Ord>(slice: &[T], x: &T) -> usize {
+  upper_bound_by(slice, |element| element.cmp(x))
+}
+
+// All the elements equal to `x` in a sorted slice, empty (at the place `x`
+// would go) when there are none
+pub fn equal_range<'a, T: Ord>(slice: &'a [T], x: &T) -> &'a [T] {
+  equal_range_by(slice, |element| element.cmp(x))
+}
+
+// The `_by` versions take how an element compares to what is looked for,
+// like `slice::binary_search_by`, to search by a key or in reverse order
+pub fn lower_bound_by<T>(slice: &[T], mut compare: impl FnMut(&T) -> Ordering) -> usize {
+  partition_point(slice, |element| compare(element) == Ordering::Less)
+}
+
+pub fn upper_bound_by<T>(slice: &[T], mut compare: impl FnMut(&T) -> Ordering) -> usize {
+  partition_point(slice, |element| compare(element) != Ordering::Greater)
+}
+
+pub fn equal_range_by<T>(slice: &[T], mut compare: impl FnMut(&T) -> Ordering) -> &[T] {
+  let start = lower_bound_by(slice, &mut compare);
+  let end = start + upper_bound_by(&slice[start..], &mut compare);
+  &slice[start..end]
+}
+
+// `slice` rotated left by `mid` without moving anything: read the first part
+// and then the second one
+pub fn rotated<T>(slice: &[T], mid: usize) -> (&[T], &[T]) {
+  let (front, back) = slice.split_at(mid);
+  (back, front)
+}
+
+// Rotates `slice` left by `mid` in place, so that `slice[mid]` comes first.
+// Reversing both parts and then the whole puts every element where it belongs.
+pub fn rotate_left<T>(slice: &mut [T], mid: usize) {
+  assert!(mid <= slice.len(), "rotating by more than the length");
+  slice[..mid].reverse();
+  slice[mid..].reverse();
+  slice.reverse();
+}
+
+// Moves the elements for which `pred` holds to the front, keeping the order
+// within both groups, and returns the two groups.
+// Without room for a copy it takes O(n log n): both halves are partitioned
+// and then the middle part, the second half's front group behind the first
+// half's back group, is rotated into place.
+pub fn stable_partition<T>(
+  slice: &mut [T],
+  mut pred: impl FnMut(&T) -> bool,
+) -> (&mut [T], &mut [T]) {
+  let front = partition_in_place(slice, &mut pred);
+  slice.split_at_mut(front)
+}
+
+// How many elements `pred` holds for, once they are at the front
+fn partition_in_place<T>(slice: &mut [T], pred: &mut impl FnMut(&T) -> bool) -> usize {
+  match slice.len() {
+    0 => 0,
+    1 => pred(&slice[0]) as usize,
+    len => {
+      let mid = len / 2;
+      let left = partition_in_place(&mut slice[..mid], pred);
+      let right = partition_in_place(&mut slice[mid..], pred);
+      rotate_left(&mut slice[left..mid + right], mid - left);
+      left + right
+    }
+  }
+}
+
+// The parts of `slice` between the occurrences of `separator`, like
+// `str::split` with a string: "a,,b" gives "a", "" and "b", "" gives "".
+pub fn split_on<'a, 'b, T: PartialEq>(slice: &'a [T], separator: &'b [T]) -> SplitOn<'a, 'b, T> {
+  assert!(!separator.is_empty(), "the separator must not be empty");
+  SplitOn {
+    slice: Some(slice),
+    separator,
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitOn<'a, 'b, T> {
+  // what is left to split, `None` after the last part
+  slice: Option<&'a [T]>,
+  separator: &'b [T],
+}
+
+impl<'a, 'b, T: PartialEq> Iterator for SplitOn<'a, 'b, T> {
+  type Item = &'a [T];
+
+  fn next(&mut self) -> Option<&'a [T]> {
+    let slice = self.slice?;
+    let found = slice
+      .windows(self.separator.len())
+      .position(|window| window == self.separator);
+    match found {
+      Some(at) => {
+        self.slice = Some(&slice[at + self.separator.len()..]);
+        Some(&slice[..at])
+      }
+      None => mem::take(&mut self.slice),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use proptest::prelude::*;
+
+  // `part` is a subslice of `whole`, not a copy
+  fn borrowed_from<T>(part: &[T], whole: &[T]) -> bool {
+    let whole = whole.as_ptr_range();
+    let part = part.as_ptr_range();
+    whole.start <= part.start && part.end <= whole.end
+  }
+
+  #[test]
+  fn the_lesson_array() {
+    let a = [1, 2, 3, 4, 5];
+    let pairs: Vec<&[i32]> = windows(&a, 2, 2).collect();
+    assert_eq!(pairs, [&[1, 2], &[3, 4]]);
+    assert_eq!(equal_range(&a, &3), &a[2..3]);
+    assert_eq!(equal_range(&a, &9), &[] as &[i32]);
+    assert_eq!(rotated(&a, 1), (&a[1..], &a[..1]));
+    let odd: Vec<&[i32]> = chunk_by(&a, |x, y| x % 2 == y % 2).collect();
+    assert_eq!(odd.len(), 5);
+
+    let mut b = a;
+    let (even, odd) = stable_partition(&mut b, |x| x % 2 == 0);
+    assert_eq!((&*even, &*odd), (&[2, 4][..], &[1, 3, 5][..]));
+
+    let parts: Vec<&[u8]> = split_on(b"a, b,, c", b", ").collect();
+    assert_eq!(parts, [&b"a"[..], b"b,", b"c"]);
+  }
+
+  #[test]
+  fn searching_by_key() {
+    let people = [("ann", 31), ("bob", 25), ("cat", 31), ("dan", 40)];
+    let mut by_age = people;
+    by_age.sort_by_key(|person| person.1);
+    let thirty_one = equal_range_by(&by_age, |person| person.1.cmp(&31));
+    assert_eq!(thirty_one, [("ann", 31), ("cat", 31)]);
+    assert_eq!(lower_bound_by(&by_age, |person| person.1.cmp(&30)), 1);
+    assert_eq!(upper_bound_by(&by_age, |person| person.1.cmp(&40)), 4);
+  }
+
+  #[test]
+  #[should_panic(expected = "must not be 0")]
+  fn windows_of_nothing() {
+    windows(&[1], 0, 1);
+  }
+
+  // The naive versions, which copy whatever they return
+
+  fn naive_windows(v: &[u8], size: usize, step: usize) -> Vec<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    while start + size <= v.len() {
+      out.push(v[start..start + size].to_vec());
+      start += step;
+    }
+    out
+  }
+
+  fn naive_chunks(v: &[u8], same: impl Fn(&u8, &u8) -> bool) -> Vec<Vec<u8>> {
+    let mut out: Vec<Vec<u8>> = Vec::new();
+    for x in v {
+      match out.last_mut() {
+        Some(chunk) if same(chunk.last().unwrap(), x) => chunk.push(*x),
+        _ => out.push(vec![*x]),
+      }
+    }
+    out
+  }
+
+  fn naive_split(v: &[u8], separator: &[u8]) -> Vec<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut part = Vec::new();
+    let mut i = 0;
+    while i < v.len() {
+      if v[i..].starts_with(separator) {
+        out.push(mem::take(&mut part));
+        i += separator.len();
+      } else {
+        part.push(v[i]);
+        i += 1;
+      }
+    }
+    out.push(part);
+    out
+  }
+
+  proptest! {
+    #[test]
+    fn windows_match_naive(v in prop::collection::vec(any::<u8>(), 0..50), size in 1..8usize, step in 1..8usize) {
+      let ours: Vec<&[u8]> = windows(&v, size, step).collect();
+      prop_assert!(ours.iter().all(|window| borrowed_from(window, &v)));
+      let ours: Vec<Vec<u8>> = ours.into_iter().map(<[u8]>::to_vec).collect();
+      prop_assert_eq!(ours, naive_windows(&v, size, step));
+    }
+
+    #[test]
+    fn chunks_match_naive(v in prop::collection::vec(0..6u8, 0..50)) {
+      let same = |x: &u8, y: &u8| x / 2 == y / 2;
+      let ours: Vec<&[u8]> = chunk_by(&v, same).collect();
+      prop_assert!(ours.iter().all(|chunk| borrowed_from(chunk, &v)));
+      let ours: Vec<Vec<u8>> = ours.into_iter().map(<[u8]>::to_vec).collect();
+      prop_assert_eq!(ours, naive_chunks(&v, same));
+    }
+
+    #[test]
+    fn searches_match_naive(mut v in prop::collection::vec(0..20u8, 0..50), x in 0..22u8) {
+      v.sort();
+      let less = v.iter().filter(|y| **y < x).count();
+      let equal = v.iter().filter(|y| **y == x).count();
+      prop_assert_eq!(lower_bound(&v, &x), less);
+      prop_assert_eq!(upper_bound(&v, &x), less + equal);
+      let range = equal_range(&v, &x);
+      prop_assert!(borrowed_from(range, &v));
+      prop_assert_eq!(range, &vec![x; equal][..]);
+    }
+
+    #[test]
+    fn rotations_match_naive(v in prop::collection::vec(any::<u8>(), 0..50), mid in 0..50usize) {
+      let mid = mid.min(v.len());
+      let naive: Vec<u8> = v[mid..].iter().chain(&v[..mid]).copied().collect();
+      let (first, second) = rotated(&v, mid);
+      prop_assert_eq!([first, second].concat(), naive.clone());
+      let mut in_place = v.clone();
+      rotate_left(&mut in_place, mid);
+      prop_assert_eq!(in_place, naive);
+    }
+
+    #[test]
+    fn partition_matches_naive(v in prop::collection::vec(any::<u8>(), 0..50)) {
+      // `is_multiple_of` needs Rust 1.87
+      #[allow(clippy::manual_is_multiple_of)]
+      let pred = |x: &u8| x % 3 == 0;
+      let naive_front: Vec<u8> = v.iter().copied().filter(pred).collect();
+      let naive_back: Vec<u8> = v.iter().copied().filter(|x| !pred(x)).collect();
+      let mut ours = v.clone();
+      let mut calls = 0;
+      let (front, back) = stable_partition(&mut ours, |x| {
+        calls += 1;
+        pred(x)
+      });
+      prop_assert_eq!(&*front, &naive_front[..]);
+      prop_assert_eq!(&*back, &naive_back[..]);
+      prop_assert_eq!(calls, v.len());
+    }
+
+    #[test]
+    fn splits_match_naive(v in prop::collection::vec(0..3u8, 0..50), separator in prop::collection::vec(0..3u8, 1..3)) {
+      let ours: Vec<&[u8]> = split_on(&v, &separator).collect();
+      prop_assert!(ours.iter().all(|part| borrowed_from(part, &v)));
+      let ours: Vec<Vec<u8>> = ours.into_iter().map(<[u8]>::to_vec).collect();
+      prop_assert_eq!(ours, naive_split(&v, &separator));
+    }
+  }
+}
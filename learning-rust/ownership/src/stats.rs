@@ -0,0 +1,241 @@
+// Word counts and other text statistics, built on the tokenizer.
+// This is where returning slices pays off. The lesson's first `first_word`
+// returned an index, which is only meaningful as long as nobody changes the
+// string, and to use the word we would have to slice (or copy) it ourselves.
+// `tokenizer::words` hands out `&str`s that borrow from the line we read, so
+// counting a word that was seen before costs a hash lookup and nothing else:
+// the only words that get copied are the ones seen for the first time.
+//
+// Input is read in blocks into one reused buffer and counted a line at a time.
+// A line longer than a block is counted up to its last space and the rest is
+// carried over to the next block, so a file of hundreds of megabytes never
+// has to fit into memory, not even as a single line: only its distinct words
+// (and the longest of them) do.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use crate::tokenizer;
+
+// How much is taken from the reader at once
+const BLOCK: usize = 64 * 1024;
+
+#[derive(Debug, Default)]
+pub struct TextStats {
+  pub lines: usize,
+  pub words: usize,
+  pub chars: usize,
+  pub bytes: usize,
+  // chars of all the words together, for the average word length
+  word_chars: usize,
+  counts: HashMap<String, usize>,
+  // count "The" and "the" as the same word
+  ignore_case: bool,
+  // where case folded words are built, reused from word to word
+  folded: String,
+}
+
+impl TextStats {
+  pub fn new() -> TextStats {
+    TextStats::default()
+  }
+
+  pub fn ignore_case(mut self, ignore_case: bool) -> TextStats {
+    self.ignore_case = ignore_case;
+    self
+  }
+
+  // Reads `reader` to the end, a block at a time.
+  // Bytes that are not UTF-8 are counted but replaced before looking for words.
+  pub fn read<R: BufRead>(&mut self, mut reader: R) -> io::Result<()> {
+    // what was read but not counted yet: the start of a line
+    let mut pending = Vec::new();
+    // part of the current line was counted already
+    let mut in_line = false;
+    loop {
+      let block = match reader.fill_buf() {
+        Ok(block) => block,
+        Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+        Err(error) => return Err(error),
+      };
+      if block.is_empty() {
+        break;
+      }
+      let taken = block.len().min(BLOCK);
+      pending.extend_from_slice(&block[..taken]);
+      reader.consume(taken);
+
+      let mut start = 0;
+      while let Some(end) = pending[start..].iter().position(|&b| b == b'\n') {
+        self.count_text(&pending[start..=start + end]);
+        self.lines += 1;
+        in_line = false;
+        start += end + 1;
+      }
+      // A space is never part of a word, and it ends any UTF-8 sequence, so
+      // cutting after one counts the same words and chars as the whole line
+      if pending.len() - start >= BLOCK {
+        if let Some(space) = pending[start..].iter().rposition(u8::is_ascii_whitespace) {
+          self.count_text(&pending[start..=start + space]);
+          in_line = true;
+          start += space + 1;
+        }
+      }
+      pending.drain(..start);
+    }
+    // the last line, when it does not end in a newline
+    if !pending.is_empty() || in_line {
+      self.count_text(&pending);
+      self.lines += 1;
+    }
+    Ok(())
+  }
+
+  // Adds a whole text, the same as reading it
+  pub fn add_text(&mut self, text: &str) {
+    self
+      .read(text.as_bytes())
+      .expect("reading from a slice does not fail");
+  }
+
+  // Counts a line, or a part of one that ends after a space
+  fn count_text(&mut self, bytes: &[u8]) {
+    self.bytes += bytes.len();
+    // borrows the bytes when they are valid UTF-8, copies only when they are not
+    let text: Cow<str> = String::from_utf8_lossy(bytes);
+    self.chars += text.chars().count();
+    for word in tokenizer::words(&text) {
+      self.words += 1;
+      self.word_chars += word.chars().count();
+      self.count_word(word);
+    }
+  }
+
+  fn count_word(&mut self, word: &str) {
+    let word = if self.ignore_case && word.chars().any(char::is_uppercase) {
+      self.folded.clear();
+      self
+        .folded
+        .extend(word.chars().flat_map(char::to_lowercase));
+      self.folded.as_str()
+    } else {
+      word
+    };
+    // `get_mut` takes a `&str`, a `String` key is only made for a new word
+    match self.counts.get_mut(word) {
+      Some(count) => *count += 1,
+      None => {
+        self.counts.insert(word.to_string(), 1);
+      }
+    }
+  }
+
+  // Number of different words
+  pub fn distinct(&self) -> usize {
+    self.counts.len()
+  }
+
+  pub fn count(&self, word: &str) -> usize {
+    self.counts.get(word).copied().unwrap_or(0)
+  }
+
+  // Average number of chars per word, 0 without words
+  pub fn average_word_length(&self) -> f64 {
+    if self.words == 0 {
+      0.0
+    } else {
+      self.word_chars as f64 / self.words as f64
+    }
+  }
+
+  // The `n` most frequent words, ties in alphabetical order.
+  // The words are borrowed from `self`, nothing is copied to report them.
+  pub fn top(&self, n: usize) -> Vec<(&str, usize)> {
+    let mut counts: Vec<(&str, usize)> = self
+      .counts
+      .iter()
+      .map(|(word, count)| (word.as_str(), *count))
+      .collect();
+    counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts.truncate(n);
+    counts
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn counts_lines_words_chars_and_bytes() {
+    let mut stats = TextStats::new();
+    stats.add_text("the crab\r\nand the crab's shell\nnaïve");
+    assert_eq!(stats.lines, 3);
+    assert_eq!(stats.words, 7);
+    assert_eq!(stats.chars, 36);
+    assert_eq!(stats.bytes, 37);
+    assert_eq!(stats.distinct(), 6);
+    assert_eq!(stats.average_word_length(), 29.0 / 7.0);
+  }
+
+  #[test]
+  fn top_words_break_ties_alphabetically() {
+    let mut stats = TextStats::new();
+    stats.add_text("b a c b a b\n");
+    assert_eq!(stats.top(2), [("b", 3), ("a", 2)]);
+    assert_eq!(stats.top(10).len(), 3);
+  }
+
+  #[test]
+  fn case_can_be_ignored() {
+    let mut stats = TextStats::new().ignore_case(true);
+    stats.add_text("The THE the Été été");
+    assert_eq!(stats.count("the"), 3);
+    assert_eq!(stats.count("été"), 2);
+    assert_eq!(stats.distinct(), 2);
+  }
+
+  #[test]
+  fn a_line_longer_than_a_block_is_counted_in_parts() {
+    let repeats = BLOCK / 10;
+    let line = "naïve crab's shell ".repeat(repeats);
+    let mut stats = TextStats::new();
+    stats.add_text(&format!("{}\n{}", line, line));
+    assert_eq!(stats.lines, 2);
+    assert_eq!(stats.words, 6 * repeats);
+    assert_eq!(stats.count("crab's"), 2 * repeats);
+    assert_eq!(stats.chars, 2 * line.chars().count() + 1);
+    assert_eq!(stats.bytes, 2 * line.len() + 1);
+
+    // a line that is one block exactly, cut at its very end
+    let mut stats = TextStats::new();
+    stats.add_text(&"abc ".repeat(BLOCK / 4));
+    assert_eq!(stats.bytes, BLOCK);
+    assert_eq!(stats.lines, 1);
+    assert_eq!(stats.words, BLOCK / 4);
+  }
+
+  #[test]
+  fn chars_split_between_reads_are_put_back_together() {
+    let text = "Grüße, 東京\ncafé ok";
+    let mut stats = TextStats::new();
+    // hands out a byte at a time
+    stats
+      .read(io::BufReader::with_capacity(1, text.as_bytes()))
+      .unwrap();
+    assert_eq!(stats.lines, 2);
+    assert_eq!(stats.words, 4);
+    assert_eq!(stats.chars, text.chars().count());
+    assert_eq!(stats.count("東京"), 1);
+  }
+
+  #[test]
+  fn invalid_utf8_is_counted_as_bytes() {
+    let mut stats = TextStats::new();
+    stats.read(&b"caf\xe9 ok\n"[..]).unwrap();
+    assert_eq!(stats.bytes, 8);
+    assert_eq!(stats.lines, 1);
+    assert_eq!(stats.count("ok"), 1);
+  }
+}
@@ -0,0 +1,200 @@
+// Splitting text into words, the grown up version of `first_word`.
+// Every function takes a `&str` and hands back slices of it, no word is ever
+// copied: the returned `&str`s borrow from the input, so the compiler keeps
+// the input alive (and unchanged) for as long as we hold on to a word.
+//
+// A word is a run of letters, digits, underscores and the combining marks
+// attached to them. Everything else separates words: any Unicode whitespace
+// (tabs, newlines, no-break spaces, ideographic spaces) and punctuation.
+// Apostrophes and hyphens between two word characters stay inside the word,
+// so "don't" and "well-known" are one word each.
+
+// Characters that join two words into one when they sit between word characters
+const JOINERS: [char; 4] = ['\'', '’', '-', '‐'];
+
+// Letters, digits and `_`
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+// Combining marks, zero width joiners and variation selectors only decorate the
+// character in front of them: `e\u{301}` is an `é` and belongs to the word
+fn is_mark(c: char) -> bool {
+  matches!(c,
+    '\u{0300}'..='\u{036F}'
+    | '\u{1AB0}'..='\u{1AFF}'
+    | '\u{1DC0}'..='\u{1DFF}'
+    | '\u{20D0}'..='\u{20FF}'
+    | '\u{FE00}'..='\u{FE0F}'
+    | '\u{FE20}'..='\u{FE2F}'
+    | '\u{200C}'..='\u{200D}')
+}
+
+// The first word of `s`, `None` when there is none
+pub fn first_word(s: &str) -> Option<&str> {
+  words(s).next()
+}
+
+// The last word, found from the back without walking over the whole text
+pub fn last_word(s: &str) -> Option<&str> {
+  words(s).next_back()
+}
+
+// The word at `n`, counted from 0
+pub fn nth_word(s: &str, n: usize) -> Option<&str> {
+  words(s).nth(n)
+}
+
+// An iterator over the words of `s`, from either end
+pub fn words(s: &str) -> Words<'_> {
+  Words {
+    text: s,
+    front: 0,
+    back: s.len(),
+  }
+}
+
+// The words together with the byte offset where each one starts in `s`
+pub fn word_indices(s: &str) -> impl DoubleEndedIterator<Item = (usize, &str)> {
+  // a word is a slice of `s`, its offset is just the distance between the pointers
+  words(s).map(move |word| (word.as_ptr() as usize - s.as_ptr() as usize, word))
+}
+
+// Returned by `words`. It only keeps the text and two byte positions, the
+// part between them has not been handed out yet
+#[derive(Debug, Clone)]
+pub struct Words<'a> {
+  text: &'a str,
+  front: usize,
+  back: usize,
+}
+
+impl<'a> Words<'a> {
+  // Does the char at byte `i` belong to a word, looking at its neighbours
+  // for joiners and marks
+  fn belongs(&self, i: usize, c: char) -> bool {
+    if is_word_char(c) {
+      return true;
+    }
+    let before = self.text[..i].chars().next_back();
+    if is_mark(c) {
+      // over any marks in between, back to what they all decorate
+      let decorated = self.text[..i].chars().rev().find(|c| !is_mark(*c));
+      return decorated.is_some_and(is_word_char);
+    }
+    if JOINERS.contains(&c) {
+      let after = self.text[i + c.len_utf8()..].chars().next();
+      return before.is_some_and(is_word_char) && after.is_some_and(is_word_char);
+    }
+    false
+  }
+}
+
+impl<'a> Iterator for Words<'a> {
+  type Item = &'a str;
+
+  fn next(&mut self) -> Option<&'a str> {
+    let rest = &self.text[self.front..self.back];
+    let mut start = None;
+    for (offset, c) in rest.char_indices() {
+      let i = self.front + offset;
+      match (start, self.belongs(i, c)) {
+        (None, true) => start = Some(i),
+        (Some(begin), false) => {
+          self.front = i;
+          return Some(&self.text[begin..i]);
+        }
+        _ => {}
+      }
+    }
+    self.front = self.back;
+    start.map(|begin| &self.text[begin..self.back])
+  }
+}
+
+impl<'a> DoubleEndedIterator for Words<'a> {
+  fn next_back(&mut self) -> Option<&'a str> {
+    let rest = &self.text[self.front..self.back];
+    let mut end = None;
+    for (offset, c) in rest.char_indices().rev() {
+      let i = self.front + offset;
+      match (end, self.belongs(i, c)) {
+        (None, true) => end = Some(i + c.len_utf8()),
+        (Some(finish), false) => {
+          self.back = i + c.len_utf8();
+          return Some(&self.text[self.back..finish]);
+        }
+        _ => {}
+      }
+    }
+    self.back = self.front;
+    end.map(|finish| &self.text[self.front..finish])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn any_unicode_whitespace_separates_words() {
+    let text = "hello\tworld\nfrom\u{00A0}the\u{3000}crab";
+    let all: Vec<&str> = words(text).collect();
+    assert_eq!(all, ["hello", "world", "from", "the", "crab"]);
+    assert_eq!(first_word(text), Some("hello"));
+    assert_eq!(last_word(text), Some("crab"));
+    assert_eq!(nth_word(text, 2), Some("from"));
+    assert_eq!(nth_word(text, 5), None);
+  }
+
+  #[test]
+  fn punctuation_separates_but_joiners_stay_inside() {
+    let text = "“Don't panic!” — a well-known, rock-solid 'advice'...";
+    let all: Vec<&str> = words(text).collect();
+    assert_eq!(
+      all,
+      ["Don't", "panic", "a", "well-known", "rock-solid", "advice"]
+    );
+  }
+
+  #[test]
+  fn letters_of_any_script_and_their_marks_are_words() {
+    let text = "cafe\u{301} naïve, Grüße; 東京 δ_1";
+    let all: Vec<&str> = words(text).collect();
+    assert_eq!(all, ["cafe\u{301}", "naïve", "Grüße", "東京", "δ_1"]);
+  }
+
+  #[test]
+  fn marks_without_a_letter_are_not_words() {
+    // two marks after a space decorate nothing, the second one included
+    let text = "a \u{301}\u{302} b e\u{301}\u{302} \u{301}";
+    let all: Vec<&str> = words(text).collect();
+    assert_eq!(all, ["a", "b", "e\u{301}\u{302}"]);
+    let backwards: Vec<&str> = words(text).rev().collect();
+    assert_eq!(backwards, ["e\u{301}\u{302}", "b", "a"]);
+    assert_eq!(first_word("\u{301}\u{302}\u{303}"), None);
+  }
+
+  #[test]
+  fn both_ends_meet_in_the_middle() {
+    let mut it = words("one, two three");
+    assert_eq!(it.next_back(), Some("three"));
+    assert_eq!(it.next(), Some("one"));
+    assert_eq!(it.next_back(), Some("two"));
+    assert_eq!(it.next(), None);
+    assert_eq!(it.next_back(), None);
+
+    assert_eq!(first_word("  ... "), None);
+    assert_eq!(last_word(""), None);
+  }
+
+  #[test]
+  fn words_are_slices_of_the_input() {
+    let text = String::from("borrow, don't copy");
+    let indices: Vec<(usize, &str)> = word_indices(&text).collect();
+    assert_eq!(indices, [(0, "borrow"), (8, "don't"), (14, "copy")]);
+    for (start, word) in indices {
+      assert!(std::ptr::eq(word, &text[start..start + word.len()]));
+    }
+  }
+}
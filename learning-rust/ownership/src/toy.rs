@@ -0,0 +1,204 @@
+// A toy language to play with the ownership rules.
+// It has just enough Rust to get into trouble: `let`, `let mut`, strings that
+// move and integers that copy, `&` and `&mut`, `*r` and blocks.
+//
+//     let mut s = "hello";
+//     let r1 = &s;
+//     let r2 = &mut s;   // error[E0502]
+//     print(r1);
+//
+// A program is checked before it runs, and the checker reports the same
+// errors rustc would, with the same codes, pointing at the same places.
+//
+// Built in are `print(a, b, ...)`, which only borrows its arguments like
+// `println!` does, and `drop(x)`. Strings have `len()`, `clone()` and
+// `push_str(e)`, the argument is a literal or a `&String`.
+
+mod ast;
+mod checker;
+mod diagnostic;
+mod interp;
+mod lexer;
+mod parser;
+
+use std::io::Write;
+
+pub use ast::Stmt;
+pub use diagnostic::{Diagnostic, Label, Span};
+
+// Parses and checks a program, all the errors are reported at once
+pub fn check(source: &str) -> Result<Vec<Stmt>, Vec<Diagnostic>> {
+  let tokens = lexer::tokenize(source).map_err(|error| vec![error])?;
+  let program = parser::parse(tokens).map_err(|error| vec![error])?;
+  checker::check(&program)?;
+  Ok(program)
+}
+
+// Checks a program and runs it, `print` writes to `out`
+pub fn run(source: &str, out: &mut impl Write) -> Result<(), Vec<Diagnostic>> {
+  let program = check(source)?;
+  interp::run(&program, out).map_err(|error| vec![error])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn codes(source: &str) -> Vec<&'static str> {
+    match check(source) {
+      Ok(_) => Vec::new(),
+      Err(errors) => errors
+        .iter()
+        .map(|error| error.code.unwrap_or("-"))
+        .collect(),
+    }
+  }
+
+  fn output(source: &str) -> String {
+    let mut out = Vec::new();
+    if let Err(errors) = run(source, &mut out) {
+      panic!("{}", errors[0].render(source, "test.toy"));
+    }
+    String::from_utf8(out).unwrap()
+  }
+
+  #[test]
+  fn runs_valid_programs() {
+    let source = "
+      let mut s = \"hello\";
+      {
+        let r = &mut s;
+        r.push_str(\", world\");
+      }
+      let r1 = &s;
+      let r2 = &s;
+      print(r1, r2.len());
+      let x = 5;
+      let y = x;
+      print(x + y, s);
+    ";
+    assert_eq!(output(source), "hello, world 12\n10 hello, world\n");
+  }
+
+  #[test]
+  fn borrows_end_at_their_last_use() {
+    // the book's example: r1 and r2 are not used after the print
+    let source = "
+      let mut s = \"hello\";
+      let r1 = &s;
+      let r2 = &s;
+      print(r1, r2);
+      let r3 = &mut s;
+      r3.push_str(\"!\");
+      print(s);
+    ";
+    assert_eq!(output(source), "hello hello\nhello!\n");
+  }
+
+  #[test]
+  fn use_after_move() {
+    let source = "let s1 = \"hello\";\nlet s2 = s1;\nprint(s1);\n";
+    let errors = check(source).unwrap_err();
+    assert_eq!(
+      errors[0].render(source, "main.toy"),
+      "\
+error[E0382]: borrow of moved value: `s1`
+ --> main.toy:3:7
+  |
+1 | let s1 = \"hello\";
+  |     -- move occurs because `s1` has type `String`, which does not implement the `Copy` trait
+2 | let s2 = s1;
+  |          -- value moved here
+3 | print(s1);
+  |       ^^ value borrowed here after move
+"
+    );
+    assert_eq!(codes("let s = \"a\"; drop(s); let t = s;"), ["E0382"]);
+    assert_eq!(
+      codes("let s = \"a\"; let t = s.clone(); print(s, t);"),
+      Vec::<&str>::new()
+    );
+  }
+
+  #[test]
+  fn one_mutable_or_many_shared() {
+    assert_eq!(
+      codes("let mut s = \"a\"; let r1 = &mut s; let r2 = &mut s; print(r1, r2);"),
+      ["E0499"]
+    );
+    assert_eq!(
+      codes("let mut s = \"a\"; let r1 = &s; let r2 = &mut s; print(r1, r2);"),
+      ["E0502"]
+    );
+    assert_eq!(
+      codes("let mut s = \"a\"; let r = &mut s; print(s); r.push_str(\"b\");"),
+      ["E0502"]
+    );
+    assert_eq!(
+      codes("let mut x = 1; let r = &mut x; let y = x; *r = 2;"),
+      ["E0503"]
+    );
+    assert_eq!(
+      codes("let s = \"a\"; let r = &s; let t = s; print(r);"),
+      ["E0505"]
+    );
+    assert_eq!(
+      codes("let mut x = 1; let r = &x; x = 2; print(r);"),
+      ["E0506"]
+    );
+  }
+
+  #[test]
+  fn references_must_not_dangle() {
+    let source = "let r;\n{\n  let x = 5;\n  r = &x;\n}\nprint(r);\n";
+    let errors = check(source).unwrap_err();
+    assert_eq!(
+      errors[0].render(source, "main.toy"),
+      "\
+error[E0597]: `x` does not live long enough
+ --> main.toy:4:7
+  |
+3 |   let x = 5;
+  |       - binding `x` declared here
+4 |   r = &x;
+  |       ^^ borrowed value does not live long enough
+5 | }
+  | - `x` dropped here while still borrowed
+6 | print(r);
+  |       - borrow later used here
+"
+    );
+  }
+
+  #[test]
+  fn mutability_is_explicit() {
+    assert_eq!(codes("let s = \"a\"; let r = &mut s;"), ["E0596"]);
+    assert_eq!(codes("let s = \"a\"; s.push_str(\"b\");"), ["E0596"]);
+    assert_eq!(
+      codes("let s = \"a\"; let r = &s; r.push_str(\"b\");"),
+      ["E0596"]
+    );
+    assert_eq!(codes("let x = 1; let r = &x; *r = 2;"), ["E0594"]);
+    assert_eq!(codes("let x = 1; x = 2;"), ["E0384"]);
+    assert_eq!(codes("let x; x = 2; print(x);"), Vec::<&str>::new());
+    assert_eq!(codes("let x; print(x);"), ["E0381"]);
+  }
+
+  #[test]
+  fn names_and_types() {
+    assert_eq!(codes("print(y);"), ["E0425"]);
+    assert_eq!(codes("let x = 1; x.len();"), ["E0599"]);
+    assert_eq!(codes("let mut x = 1; x = \"a\";"), ["E0308"]);
+    assert_eq!(codes("let x = 1; let y = *x;"), ["E0614"]);
+    assert_eq!(codes("let s = \"a\"; let r = &s; let t = *r;"), ["E0507"]);
+    assert_eq!(codes("let s = \"a\"; let n = s + 1;"), ["E0369"]);
+    assert_eq!(codes("let s = \"a\" print(s);"), ["-"]);
+  }
+
+  #[test]
+  fn overflow_is_a_runtime_error() {
+    let source = "let x = 9223372036854775807; print(x + 1);";
+    let errors = run(source, &mut Vec::new()).unwrap_err();
+    assert_eq!(errors[0].message, "attempt to add with overflow");
+  }
+}
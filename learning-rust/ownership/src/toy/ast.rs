@@ -0,0 +1,80 @@
+// The syntax tree of a toy program.
+
+use super::diagnostic::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+  pub name: String,
+  pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+  // `let x;`, `let mut x = e;`
+  Let {
+    name: Ident,
+    mutable: bool,
+    init: Option<Expr>,
+  },
+  // `x = e;` or `*x = e;`
+  Assign {
+    target: Ident,
+    deref: bool,
+    value: Expr,
+    span: Span,
+  },
+  // `print(x);`, `s.push_str("!");`
+  Expr(Expr),
+  Block(Block),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+  pub stmts: Vec<Stmt>,
+  // the closing `}`, where the block's variables are dropped
+  pub close: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+  pub kind: ExprKind,
+  pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+  Int(i64),
+  // string literals make a new `String`, like `String::from("...")`
+  Str(String),
+  Var(Ident),
+  // `&x`, `&mut x`
+  Borrow {
+    mutable: bool,
+    name: Ident,
+  },
+  // `*x`
+  Deref(Ident),
+  // `a + b`, `a - b`, on integers
+  Binary {
+    op: BinaryOp,
+    left: Box<Expr>,
+    right: Box<Expr>,
+  },
+  // `print(a, b)`, `drop(x)`
+  Call {
+    name: Ident,
+    args: Vec<Expr>,
+  },
+  // `s.len()`, `s.clone()`, `s.push_str(e)`
+  Method {
+    receiver: Ident,
+    method: Ident,
+    args: Vec<Expr>,
+  },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+  Add,
+  Sub,
+}
@@ -0,0 +1,826 @@
+// The borrow checker.
+// It enforces the rules from the end of the ownership lesson:
+// * a value has one owner, after a move the old name cannot be used
+// * at any time there is either one mutable reference or any number of
+//   shared ones
+// * references must always be valid, nothing is dropped while borrowed
+//
+// The check runs in two passes. The first one resolves names, works out the
+// types and flattens the program into a list of events (`s` is moved here,
+// `r` borrows `x` there, `x` is dropped at that `}`). The language has no
+// branches or loops, so the list is exactly the order things happen in.
+//
+// The second pass walks the events and looks for conflicts. A borrow lives
+// from the `&` to the last use of a variable holding it, like rustc's
+// non-lexical lifetimes: a reference that is never used again stops counting.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::ast::{Block, Expr, ExprKind, Ident, Stmt};
+use super::diagnostic::{Diagnostic, Span};
+
+type VarId = usize;
+type LoanId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ty {
+  Int,
+  Str,
+  Ref { mutable: bool, to: Box<Ty> },
+  Unit,
+  // a `let x;` that was not assigned yet
+  Unknown,
+}
+
+impl Ty {
+  fn is_copy(&self) -> bool {
+    matches!(self, Ty::Int | Ty::Unit | Ty::Ref { mutable: false, .. })
+  }
+}
+
+impl fmt::Display for Ty {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Ty::Int => write!(f, "i64"),
+      Ty::Str => write!(f, "String"),
+      Ty::Ref { mutable: true, to } => write!(f, "&mut {}", to),
+      Ty::Ref { mutable: false, to } => write!(f, "&{}", to),
+      Ty::Unit => write!(f, "()"),
+      Ty::Unknown => write!(f, "_"),
+    }
+  }
+}
+
+struct Var {
+  name: String,
+  mutable: bool,
+  // the name in `let`, for "binding declared here" labels
+  decl: Span,
+  ty: Ty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Access {
+  // reading a `Copy` value
+  Copy,
+  Move,
+  // `&x` / `&mut x`, `loan` is `None` for a borrow that only lasts for the
+  // expression, like the receiver of `s.len()`
+  Borrow { mutable: bool, loan: Option<LoanId> },
+  // `let x = e;`
+  Init,
+  // `x = e;`
+  Assign,
+  // reading or writing through the reference stored in the variable
+  Through { write: bool },
+  // the variable goes out of scope
+  Dead,
+}
+
+impl Access {
+  // Does the access use the variable's value (and so any borrow it holds)
+  fn is_use(self) -> bool {
+    !matches!(self, Access::Init | Access::Assign | Access::Dead)
+  }
+}
+
+#[derive(Debug)]
+struct Event {
+  var: VarId,
+  access: Access,
+  span: Span,
+}
+
+struct Loan {
+  var: VarId,
+  mutable: bool,
+  span: Span,
+  // the event that created it
+  at: usize,
+  holders: Vec<Holder>,
+}
+
+// A variable that holds (a copy of) a reference, from event `from` until it
+// is overwritten at event `until`
+struct Holder {
+  var: VarId,
+  from: usize,
+  until: Option<usize>,
+}
+
+// What evaluating an expression gives: its type and the loans it carries
+struct Value {
+  ty: Ty,
+  loans: Vec<LoanId>,
+}
+
+impl Value {
+  fn plain(ty: Ty) -> Value {
+    Value {
+      ty,
+      loans: Vec::new(),
+    }
+  }
+}
+
+pub fn check(program: &[Stmt]) -> Result<(), Vec<Diagnostic>> {
+  let mut checker = Checker::default();
+  checker.scopes.push(Vec::new());
+  for stmt in program {
+    checker.stmt(stmt);
+  }
+  // like rustc, borrows are only looked at in a program that type checks
+  if checker.diagnostics.is_empty() {
+    checker.borrows();
+  }
+
+  let mut diagnostics = checker.diagnostics;
+  if diagnostics.is_empty() {
+    Ok(())
+  } else {
+    diagnostics.sort_by_key(|diagnostic| diagnostic.primary_span());
+    Err(diagnostics)
+  }
+}
+
+#[derive(Default)]
+struct Checker {
+  vars: Vec<Var>,
+  // the variables visible in every open block, innermost last
+  scopes: Vec<Vec<(String, VarId)>>,
+  events: Vec<Event>,
+  loans: Vec<Loan>,
+  // the loans each variable holds right now (first pass)
+  holdings: HashMap<VarId, Vec<LoanId>>,
+  diagnostics: Vec<Diagnostic>,
+}
+
+// First pass: names, types and events
+impl Checker {
+  fn event(&mut self, var: VarId, access: Access, span: Span) {
+    self.events.push(Event { var, access, span });
+  }
+
+  fn error(&mut self, diagnostic: Diagnostic) {
+    self.diagnostics.push(diagnostic);
+  }
+
+  fn lookup(&mut self, name: &Ident) -> Option<VarId> {
+    let found = self
+      .scopes
+      .iter()
+      .rev()
+      .flat_map(|scope| scope.iter().rev())
+      .find(|(candidate, _)| *candidate == name.name)
+      .map(|(_, id)| *id);
+    if found.is_none() {
+      self.error(Diagnostic::error(
+        Some("E0425"),
+        &format!("cannot find value `{}` in this scope", name.name),
+        name.span,
+        "not found in this scope",
+      ));
+    }
+    found
+  }
+
+  fn stmt(&mut self, stmt: &Stmt) {
+    match stmt {
+      Stmt::Let {
+        name,
+        mutable,
+        init,
+      } => {
+        let value = init.as_ref().map(|init| self.expr(init));
+        let id = self.vars.len();
+        self.vars.push(Var {
+          name: name.name.clone(),
+          mutable: *mutable,
+          decl: name.span,
+          ty: value.as_ref().map_or(Ty::Unknown, |value| value.ty.clone()),
+        });
+        if let Some(value) = value {
+          self.event(id, Access::Init, name.span);
+          self.hold(id, value.loans);
+        }
+        self
+          .scopes
+          .last_mut()
+          .expect("there is always a scope")
+          .push((name.name.clone(), id));
+      }
+      Stmt::Assign {
+        target,
+        deref,
+        value,
+        span,
+      } => {
+        let value = self.expr(value);
+        let Some(id) = self.lookup(target) else {
+          return;
+        };
+        if *deref {
+          self.assign_through(id, target, value, *span);
+        } else {
+          self.assign(id, value, *span);
+        }
+      }
+      Stmt::Expr(expr) => {
+        self.expr(expr);
+      }
+      Stmt::Block(block) => self.block(block),
+    }
+  }
+
+  fn block(&mut self, block: &Block) {
+    self.scopes.push(Vec::new());
+    for stmt in &block.stmts {
+      self.stmt(stmt);
+    }
+    let scope = self.scopes.pop().expect("pushed above");
+    // dropped in reverse order of declaration
+    for (_, id) in scope.into_iter().rev() {
+      if self.vars[id].ty != Ty::Unknown {
+        self.event(id, Access::Dead, block.close);
+      }
+    }
+  }
+
+  fn assign(&mut self, id: VarId, value: Value, span: Span) {
+    let expected = self.vars[id].ty.clone();
+    if expected == Ty::Unknown {
+      self.vars[id].ty = value.ty.clone();
+    } else if expected != value.ty {
+      self.mismatch(&expected, &value.ty, span);
+      return;
+    }
+    self.event(id, Access::Assign, span);
+    // the old reference in the variable is gone, it holds the new one
+    let now = self.events.len() - 1;
+    for loan in self.holdings.remove(&id).unwrap_or_default() {
+      for holder in &mut self.loans[loan].holders {
+        if holder.var == id && holder.until.is_none() {
+          holder.until = Some(now);
+        }
+      }
+    }
+    self.hold(id, value.loans);
+  }
+
+  // `*r = e;`
+  fn assign_through(&mut self, id: VarId, target: &Ident, value: Value, span: Span) {
+    match self.vars[id].ty.clone() {
+      Ty::Ref { mutable: true, to } => {
+        if *to != value.ty {
+          self.mismatch(&to, &value.ty, span);
+        }
+        self.event(id, Access::Through { write: true }, target.span);
+      }
+      Ty::Ref { mutable: false, .. } => self.error(
+        Diagnostic::error(
+          Some("E0594"),
+          &format!(
+            "cannot assign to `*{}`, which is behind a `&` reference",
+            target.name
+          ),
+          span,
+          &format!(
+            "`{}` is a `&` reference, so the data it refers to cannot be written",
+            target.name
+          ),
+        )
+        .label(
+          self.vars[id].decl,
+          "help: make this a mutable reference with `&mut`",
+        ),
+      ),
+      other => self.not_a_reference(&other, target.span),
+    }
+  }
+
+  // `id` now holds `loans` too
+  fn hold(&mut self, id: VarId, loans: Vec<LoanId>) {
+    let from = self.events.len().saturating_sub(1);
+    for &loan in &loans {
+      self.loans[loan].holders.push(Holder {
+        var: id,
+        from,
+        until: None,
+      });
+    }
+    self.holdings.entry(id).or_default().extend(loans);
+  }
+
+  fn expr(&mut self, expr: &Expr) -> Value {
+    match &expr.kind {
+      ExprKind::Int(_) => Value::plain(Ty::Int),
+      ExprKind::Str(_) => Value::plain(Ty::Str),
+      ExprKind::Var(name) => {
+        let Some(id) = self.lookup(name) else {
+          return Value::plain(Ty::Unknown);
+        };
+        let ty = self.vars[id].ty.clone();
+        let access = if ty.is_copy() {
+          Access::Copy
+        } else {
+          Access::Move
+        };
+        self.event(id, access, name.span);
+        Value {
+          ty,
+          loans: self.holdings.get(&id).cloned().unwrap_or_default(),
+        }
+      }
+      ExprKind::Borrow { mutable, name } => {
+        let Some(id) = self.lookup(name) else {
+          return Value::plain(Ty::Unknown);
+        };
+        let loan = self.loans.len();
+        self.loans.push(Loan {
+          var: id,
+          mutable: *mutable,
+          span: expr.span,
+          at: self.events.len(),
+          holders: Vec::new(),
+        });
+        self.event(
+          id,
+          Access::Borrow {
+            mutable: *mutable,
+            loan: Some(loan),
+          },
+          expr.span,
+        );
+        // a reference to a reference keeps the inner borrows alive too
+        let mut loans = vec![loan];
+        loans.extend(self.holdings.get(&id).cloned().unwrap_or_default());
+        Value {
+          ty: Ty::Ref {
+            mutable: *mutable,
+            to: Box::new(self.vars[id].ty.clone()),
+          },
+          loans,
+        }
+      }
+      ExprKind::Deref(name) => {
+        let Some(id) = self.lookup(name) else {
+          return Value::plain(Ty::Unknown);
+        };
+        match self.vars[id].ty.clone() {
+          Ty::Ref { mutable, to } => {
+            self.event(id, Access::Through { write: false }, name.span);
+            if !to.is_copy() {
+              let kind = if mutable { "a mutable" } else { "a shared" };
+              self.error(Diagnostic::error(
+                Some("E0507"),
+                &format!("cannot move out of `*{}` which is behind {} reference", name.name, kind),
+                expr.span,
+                &format!(
+                  "move occurs because `*{}` has type `{}`, which does not implement the `Copy` trait",
+                  name.name, to
+                ),
+              ));
+            }
+            Value {
+              ty: *to,
+              loans: self.holdings.get(&id).cloned().unwrap_or_default(),
+            }
+          }
+          other => {
+            self.not_a_reference(&other, name.span);
+            Value::plain(Ty::Unknown)
+          }
+        }
+      }
+      ExprKind::Binary { op, left, right } => {
+        let left = self.expr(left);
+        let right = self.expr(right);
+        if left.ty != Ty::Int || right.ty != Ty::Int {
+          let verb = match op {
+            super::ast::BinaryOp::Add => "add",
+            super::ast::BinaryOp::Sub => "subtract",
+          };
+          let preposition = match op {
+            super::ast::BinaryOp::Add => "to",
+            super::ast::BinaryOp::Sub => "from",
+          };
+          self.error(Diagnostic::error(
+            Some("E0369"),
+            &format!(
+              "cannot {} `{}` {} `{}`",
+              verb, right.ty, preposition, left.ty
+            ),
+            expr.span,
+            "only integers can be added and subtracted",
+          ));
+        }
+        Value::plain(Ty::Int)
+      }
+      ExprKind::Call { name, args } => self.call(name, args, expr.span),
+      ExprKind::Method {
+        receiver,
+        method,
+        args,
+      } => self.method(receiver, method, args),
+    }
+  }
+
+  fn call(&mut self, name: &Ident, args: &[Expr], span: Span) -> Value {
+    match name.name.as_str() {
+      // like `println!`, the arguments are only borrowed
+      "print" => {
+        for arg in args {
+          match &arg.kind {
+            ExprKind::Var(var) => {
+              if let Some(id) = self.lookup(var) {
+                let access = if self.vars[id].ty.is_copy() {
+                  Access::Copy
+                } else {
+                  Access::Borrow {
+                    mutable: false,
+                    loan: None,
+                  }
+                };
+                self.event(id, access, var.span);
+              }
+            }
+            ExprKind::Deref(var) => {
+              if let Some(id) = self.lookup(var) {
+                match self.vars[id].ty.clone() {
+                  Ty::Ref { .. } => self.event(id, Access::Through { write: false }, var.span),
+                  other => self.not_a_reference(&other, var.span),
+                }
+              }
+            }
+            _ => {
+              self.expr(arg);
+            }
+          }
+        }
+        Value::plain(Ty::Unit)
+      }
+      // takes ownership of its argument, and that is all it does
+      "drop" => {
+        if args.len() != 1 {
+          self.error(Diagnostic::error(
+            Some("E0061"),
+            &format!(
+              "this function takes 1 argument but {} arguments were supplied",
+              args.len()
+            ),
+            span,
+            "expected 1 argument",
+          ));
+        }
+        for arg in args {
+          self.expr(arg);
+        }
+        Value::plain(Ty::Unit)
+      }
+      other => {
+        self.error(Diagnostic::error(
+          Some("E0425"),
+          &format!("cannot find function `{}` in this scope", other),
+          name.span,
+          "not found in this scope, try `print` or `drop`",
+        ));
+        Value::plain(Ty::Unknown)
+      }
+    }
+  }
+
+  // `len`, `clone` and `push_str` on a `String` or a reference to one
+  fn method(&mut self, receiver: &Ident, method: &Ident, args: &[Expr]) -> Value {
+    let arg_values: Vec<Value> = args.iter().map(|arg| self.expr(arg)).collect();
+    let Some(id) = self.lookup(receiver) else {
+      return Value::plain(Ty::Unknown);
+    };
+    let ty = self.vars[id].ty.clone();
+    let (target, through) = match &ty {
+      Ty::Ref { mutable, to } => ((**to).clone(), Some(*mutable)),
+      other => (other.clone(), None),
+    };
+
+    let (writes, result) = match (method.name.as_str(), &target) {
+      ("len", Ty::Str) => (false, Ty::Int),
+      ("clone", Ty::Str) | ("clone", Ty::Int) => (false, target.clone()),
+      ("push_str", Ty::Str) => {
+        // a literal stands in for a `&str` here
+        let ok = match (args, arg_values.first()) {
+          ([arg], _) if matches!(arg.kind, ExprKind::Str(_)) => true,
+          ([_], Some(value)) => matches!(&value.ty, Ty::Ref { to, .. } if **to == Ty::Str),
+          _ => false,
+        };
+        if !ok {
+          let found = arg_values
+            .first()
+            .map_or(Ty::Unit, |value| value.ty.clone());
+          let span = args.first().map_or(method.span, |arg| arg.span);
+          self.mismatch(
+            &Ty::Ref {
+              mutable: false,
+              to: Box::new(Ty::Str),
+            },
+            &found,
+            span,
+          );
+        }
+        (true, Ty::Unit)
+      }
+      _ => {
+        self.error(Diagnostic::error(
+          Some("E0599"),
+          &format!("no method named `{}` found for `{}`", method.name, ty),
+          method.span,
+          "method not found, try `len`, `clone` or `push_str`",
+        ));
+        return Value::plain(Ty::Unknown);
+      }
+    };
+
+    match through {
+      Some(false) if writes => self.error(
+        Diagnostic::error(
+          Some("E0596"),
+          &format!(
+            "cannot borrow `*{}` as mutable, as it is behind a `&` reference",
+            receiver.name
+          ),
+          receiver.span,
+          &format!(
+            "`{}` is a `&` reference, so the data it refers to cannot be borrowed as mutable",
+            receiver.name
+          ),
+        )
+        .label(
+          self.vars[id].decl,
+          "help: make this a mutable reference with `&mut`",
+        ),
+      ),
+      Some(_) => self.event(id, Access::Through { write: writes }, receiver.span),
+      None => self.event(
+        id,
+        Access::Borrow {
+          mutable: writes,
+          loan: None,
+        },
+        receiver.span,
+      ),
+    }
+    Value::plain(result)
+  }
+
+  fn mismatch(&mut self, expected: &Ty, found: &Ty, span: Span) {
+    self.error(Diagnostic::error(
+      Some("E0308"),
+      "mismatched types",
+      span,
+      &format!("expected `{}`, found `{}`", expected, found),
+    ));
+  }
+
+  fn not_a_reference(&mut self, ty: &Ty, span: Span) {
+    self.error(Diagnostic::error(
+      Some("E0614"),
+      &format!("type `{}` cannot be dereferenced", ty),
+      span,
+      "",
+    ));
+  }
+}
+
+// Second pass: walk the events in order and look for conflicts
+impl Checker {
+  fn borrows(&mut self) {
+    // where every variable is used, for the liveness of the loans it holds
+    let mut uses: HashMap<VarId, Vec<usize>> = HashMap::new();
+    for (at, event) in self.events.iter().enumerate() {
+      if event.access.is_use() {
+        uses.entry(event.var).or_default().push(at);
+      }
+    }
+
+    let mut moved: HashMap<VarId, Span> = HashMap::new();
+    let mut assigned: HashMap<VarId, Span> = HashMap::new();
+    let mut errors = Vec::new();
+
+    for (at, event) in self.events.iter().enumerate() {
+      let var = &self.vars[event.var];
+      let name = var.name.as_str();
+      // loans of this variable that are still needed at this point
+      let live: Vec<(&Loan, usize)> = self
+        .loans
+        .iter()
+        .filter(|loan| loan.var == event.var && loan.at < at)
+        .filter_map(|loan| self.later_use(loan, at, &uses).map(|used| (loan, used)))
+        .collect();
+      let first_mutable = live.iter().find(|(loan, _)| loan.mutable);
+      let first_shared = live.iter().find(|(loan, _)| !loan.mutable);
+      let used_span = |used: usize| self.events[used].span;
+
+      // using a value that is gone or was never there
+      if event.access.is_use() {
+        if let Some(moved_at) = moved.get(&event.var) {
+          let borrow = matches!(event.access, Access::Borrow { .. } | Access::Through { .. });
+          let (what, how) = if borrow {
+            ("borrow", "borrowed")
+          } else {
+            ("use", "used")
+          };
+          errors.push(
+            Diagnostic::error(
+              Some("E0382"),
+              &format!("{} of moved value: `{}`", what, name),
+              event.span,
+              &format!("value {} here after move", how),
+            )
+            .label(*moved_at, "value moved here")
+            .label(
+              var.decl,
+              &format!(
+                "move occurs because `{}` has type `{}`, which does not implement the `Copy` trait",
+                name, var.ty
+              ),
+            ),
+          );
+          continue;
+        }
+        if !assigned.contains_key(&event.var) {
+          errors.push(
+            Diagnostic::error(
+              Some("E0381"),
+              &format!("used binding `{}` isn't initialized", name),
+              event.span,
+              &format!("`{}` used here but it isn't initialized", name),
+            )
+            .label(var.decl, "binding declared here but left uninitialized"),
+          );
+          continue;
+        }
+      }
+
+      match event.access {
+        Access::Copy | Access::Through { .. } => {
+          if let Some((loan, used)) = first_mutable {
+            errors.push(
+              Diagnostic::error(
+                Some("E0503"),
+                &format!("cannot use `{}` because it was mutably borrowed", name),
+                event.span,
+                &format!("use of borrowed `{}`", name),
+              )
+              .label(loan.span, &format!("`{}` is borrowed here", name))
+              .label(used_span(*used), "borrow later used here"),
+            );
+          }
+        }
+        Access::Move => {
+          if let Some((loan, used)) = live.first() {
+            errors.push(
+              Diagnostic::error(
+                Some("E0505"),
+                &format!("cannot move out of `{}` because it is borrowed", name),
+                event.span,
+                &format!("move out of `{}` occurs here", name),
+              )
+              .label(loan.span, &format!("borrow of `{}` occurs here", name))
+              .label(used_span(*used), "borrow later used here"),
+            );
+          } else {
+            moved.insert(event.var, event.span);
+          }
+        }
+        Access::Borrow { mutable: false, .. } => {
+          if let Some((loan, used)) = first_mutable {
+            errors.push(
+              Diagnostic::error(
+                Some("E0502"),
+                &format!(
+                  "cannot borrow `{}` as immutable because it is also borrowed as mutable",
+                  name
+                ),
+                event.span,
+                "immutable borrow occurs here",
+              )
+              .label(loan.span, "mutable borrow occurs here")
+              .label(used_span(*used), "mutable borrow later used here"),
+            );
+          }
+        }
+        Access::Borrow { mutable: true, .. } => {
+          if !var.mutable {
+            errors.push(
+              Diagnostic::error(
+                Some("E0596"),
+                &format!(
+                  "cannot borrow `{}` as mutable, as it is not declared as mutable",
+                  name
+                ),
+                event.span,
+                "cannot borrow as mutable",
+              )
+              .label(
+                var.decl,
+                &format!("help: consider changing this to be mutable: `mut {}`", name),
+              ),
+            );
+          } else if let Some((loan, used)) = first_mutable {
+            errors.push(
+              Diagnostic::error(
+                Some("E0499"),
+                &format!(
+                  "cannot borrow `{}` as mutable more than once at a time",
+                  name
+                ),
+                event.span,
+                "second mutable borrow occurs here",
+              )
+              .label(loan.span, "first mutable borrow occurs here")
+              .label(used_span(*used), "first borrow later used here"),
+            );
+          } else if let Some((loan, used)) = first_shared {
+            errors.push(
+              Diagnostic::error(
+                Some("E0502"),
+                &format!(
+                  "cannot borrow `{}` as mutable because it is also borrowed as immutable",
+                  name
+                ),
+                event.span,
+                "mutable borrow occurs here",
+              )
+              .label(loan.span, "immutable borrow occurs here")
+              .label(used_span(*used), "immutable borrow later used here"),
+            );
+          }
+        }
+        Access::Init => {
+          assigned.insert(event.var, event.span);
+        }
+        Access::Assign => {
+          if let (false, Some(first)) = (var.mutable, assigned.get(&event.var)) {
+            errors.push(
+              Diagnostic::error(
+                Some("E0384"),
+                &format!("cannot assign twice to immutable variable `{}`", name),
+                event.span,
+                "cannot assign twice to immutable variable",
+              )
+              .label(*first, &format!("first assignment to `{}`", name))
+              .label(
+                var.decl,
+                &format!("help: consider making this binding mutable: `mut {}`", name),
+              ),
+            );
+          } else if let Some((loan, used)) = live.first() {
+            errors.push(
+              Diagnostic::error(
+                Some("E0506"),
+                &format!("cannot assign to `{}` because it is borrowed", name),
+                event.span,
+                &format!("`{}` is assigned to here but it was already borrowed", name),
+              )
+              .label(loan.span, &format!("`{}` is borrowed here", name))
+              .label(used_span(*used), "borrow later used here"),
+            );
+          }
+          moved.remove(&event.var);
+          assigned.entry(event.var).or_insert(event.span);
+        }
+        Access::Dead => {
+          if let Some((loan, used)) = live.first() {
+            errors.push(
+              Diagnostic::error(
+                Some("E0597"),
+                &format!("`{}` does not live long enough", name),
+                loan.span,
+                "borrowed value does not live long enough",
+              )
+              .label(var.decl, &format!("binding `{}` declared here", name))
+              .label(
+                event.span,
+                &format!("`{}` dropped here while still borrowed", name),
+              )
+              .label(used_span(*used), "borrow later used here"),
+            );
+          }
+        }
+      }
+    }
+    self.diagnostics.extend(errors);
+  }
+
+  // The first use, at or after event `at`, of a variable holding `loan`
+  fn later_use(&self, loan: &Loan, at: usize, uses: &HashMap<VarId, Vec<usize>>) -> Option<usize> {
+    loan
+      .holders
+      .iter()
+      .filter_map(|holder| {
+        uses.get(&holder.var)?.iter().copied().find(|&used| {
+          used >= at && used > holder.from && holder.until.is_none_or(|until| used < until)
+        })
+      })
+      .min()
+  }
+}
@@ -0,0 +1,176 @@
+// Errors pointing into the source, printed the way rustc prints them:
+//
+//     error[E0382]: borrow of moved value: `s`
+//      --> main.toy:3:7
+//       |
+//     2 | let t = s;
+//       |         - value moved here
+//     3 | print(s);
+//       |       ^ value borrowed here after move
+
+use std::fmt::Write;
+
+// Byte offsets into the source, `end` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+}
+
+impl Span {
+  pub fn new(start: usize, end: usize) -> Span {
+    Span { start, end }
+  }
+
+  // From the start of `self` to the end of `other`
+  pub fn to(self, other: Span) -> Span {
+    Span::new(self.start, other.end)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+  pub span: Span,
+  pub message: String,
+  // the primary label is underlined with `^`, the others with `-`
+  pub primary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+  // `E0382` and friends, the same codes rustc uses for the same mistake
+  pub code: Option<&'static str>,
+  pub message: String,
+  pub labels: Vec<Label>,
+  pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+  pub fn error(code: Option<&'static str>, message: &str, span: Span, label: &str) -> Diagnostic {
+    Diagnostic {
+      code,
+      message: message.to_string(),
+      labels: vec![Label {
+        span,
+        message: label.to_string(),
+        primary: true,
+      }],
+      notes: Vec::new(),
+    }
+  }
+
+  pub fn label(mut self, span: Span, message: &str) -> Diagnostic {
+    self.labels.push(Label {
+      span,
+      message: message.to_string(),
+      primary: false,
+    });
+    self
+  }
+
+  pub fn note(mut self, note: &str) -> Diagnostic {
+    self.notes.push(note.to_string());
+    self
+  }
+
+  pub fn primary_span(&self) -> Span {
+    self.labels[0].span
+  }
+
+  // The diagnostic with the source lines it points at, `name` is the file
+  // name shown after `-->`
+  pub fn render(&self, source: &str, name: &str) -> String {
+    let mut out = String::new();
+    match self.code {
+      Some(code) => writeln!(out, "error[{}]: {}", code, self.message),
+      None => writeln!(out, "error: {}", self.message),
+    }
+    .unwrap();
+
+    let mut labels: Vec<(Position, &Label)> = self
+      .labels
+      .iter()
+      .map(|label| (position(source, label.span.start), label))
+      .collect();
+    let (line, column) = labels[0].0;
+    labels.sort_by_key(|((line, column), label)| (*line, *column, !label.primary));
+
+    let last_line = labels.iter().map(|((line, _), _)| *line).max().unwrap_or(1);
+    let gutter = " ".repeat(last_line.to_string().len());
+    writeln!(out, "{}--> {}:{}:{}", gutter, name, line, column).unwrap();
+    writeln!(out, "{} |", gutter).unwrap();
+
+    let lines: Vec<&str> = source.split('\n').collect();
+    let mut previous = None;
+    for ((line, column), label) in &labels {
+      let text = lines.get(line - 1).copied().unwrap_or("");
+      if previous != Some(*line) {
+        if previous.is_some_and(|previous| line - previous > 1) {
+          writeln!(out, "...").unwrap();
+        }
+        writeln!(out, "{:>width$} | {}", line, text, width = gutter.len()).unwrap();
+        previous = Some(*line);
+      }
+      // the underline stops at the end of the line for spans that go on
+      let start = column - 1;
+      let rest = text.chars().count().saturating_sub(start);
+      let marks = source[label.span.start..label.span.end]
+        .chars()
+        .count()
+        .min(rest)
+        .max(1);
+      let mark = if label.primary { "^" } else { "-" };
+      let underline = format!("{}{}", " ".repeat(start), mark.repeat(marks));
+      if label.message.is_empty() {
+        writeln!(out, "{} | {}", gutter, underline).unwrap();
+      } else {
+        writeln!(out, "{} | {} {}", gutter, underline, label.message).unwrap();
+      }
+    }
+    for note in &self.notes {
+      writeln!(out, "{} = {}", gutter, note).unwrap();
+    }
+    out
+  }
+}
+
+// (line, column), both counted from 1, columns in chars
+type Position = (usize, usize);
+
+fn position(source: &str, offset: usize) -> Position {
+  let before = &source[..offset];
+  let line = before.matches('\n').count() + 1;
+  let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+  (line, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn labels_are_drawn_under_their_lines() {
+    let source = "let s = \"hi\";\nlet t = s;\nprint(s);\n";
+    let diagnostic = Diagnostic::error(
+      Some("E0382"),
+      "borrow of moved value: `s`",
+      Span::new(31, 32),
+      "value borrowed here after move",
+    )
+    .label(Span::new(22, 23), "value moved here")
+    .note("note: move occurs because `s` has type `String`");
+    assert_eq!(
+      diagnostic.render(source, "main.toy"),
+      "\
+error[E0382]: borrow of moved value: `s`
+ --> main.toy:3:7
+  |
+2 | let t = s;
+  |         - value moved here
+3 | print(s);
+  |       ^ value borrowed here after move
+  = note: move occurs because `s` has type `String`
+"
+    );
+  }
+}
@@ -0,0 +1,192 @@
+// Runs a program that passed the checker.
+// Every variable gets its own slot and a reference is just the slot number,
+// the checker already made sure nothing dangles, so there is nothing to
+// check here except arithmetic overflow.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, Ident, Stmt};
+use super::diagnostic::Diagnostic;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+  Int(i64),
+  Str(String),
+  Ref(usize),
+  Unit,
+}
+
+pub fn run(program: &[Stmt], out: &mut impl Write) -> Result<(), Diagnostic> {
+  let mut interp = Interp {
+    slots: Vec::new(),
+    scopes: vec![HashMap::new()],
+    out,
+  };
+  for stmt in program {
+    interp.stmt(stmt)?;
+  }
+  Ok(())
+}
+
+struct Interp<'a, W> {
+  slots: Vec<Value>,
+  scopes: Vec<HashMap<String, usize>>,
+  out: &'a mut W,
+}
+
+impl<W: Write> Interp<'_, W> {
+  fn slot(&self, name: &Ident) -> usize {
+    self
+      .scopes
+      .iter()
+      .rev()
+      .find_map(|scope| scope.get(&name.name))
+      .copied()
+      .expect("the checker resolved every name")
+  }
+
+  // Follows references down to the value they point at
+  fn target(&self, mut slot: usize) -> usize {
+    while let Value::Ref(to) = self.slots[slot] {
+      slot = to;
+    }
+    slot
+  }
+
+  fn stmt(&mut self, stmt: &Stmt) -> Result<(), Diagnostic> {
+    match stmt {
+      Stmt::Let { name, init, .. } => {
+        let value = match init {
+          Some(init) => self.expr(init)?,
+          None => Value::Unit,
+        };
+        self.slots.push(value);
+        let slot = self.slots.len() - 1;
+        self
+          .scopes
+          .last_mut()
+          .expect("there is always a scope")
+          .insert(name.name.clone(), slot);
+      }
+      Stmt::Assign {
+        target,
+        deref,
+        value,
+        ..
+      } => {
+        let value = self.expr(value)?;
+        let mut slot = self.slot(target);
+        if *deref {
+          let Value::Ref(to) = self.slots[slot] else {
+            unreachable!("the checker only allows `*r = e` on references");
+          };
+          slot = to;
+        }
+        self.slots[slot] = value;
+      }
+      Stmt::Expr(expr) => {
+        self.expr(expr)?;
+      }
+      Stmt::Block(block) => self.block(block)?,
+    }
+    Ok(())
+  }
+
+  fn block(&mut self, block: &Block) -> Result<(), Diagnostic> {
+    self.scopes.push(HashMap::new());
+    let result = block.stmts.iter().try_for_each(|stmt| self.stmt(stmt));
+    self.scopes.pop();
+    result
+  }
+
+  fn expr(&mut self, expr: &Expr) -> Result<Value, Diagnostic> {
+    let value = match &expr.kind {
+      ExprKind::Int(value) => Value::Int(*value),
+      ExprKind::Str(text) => Value::Str(text.clone()),
+      ExprKind::Var(name) => self.slots[self.slot(name)].clone(),
+      ExprKind::Borrow { name, .. } => Value::Ref(self.slot(name)),
+      ExprKind::Deref(name) => match self.slots[self.slot(name)] {
+        Value::Ref(to) => self.slots[to].clone(),
+        _ => unreachable!("the checker only allows `*r` on references"),
+      },
+      ExprKind::Binary { op, left, right } => {
+        let left = self.int(left)?;
+        let right = self.int(right)?;
+        let (result, verb) = match op {
+          BinaryOp::Add => (left.checked_add(right), "add"),
+          BinaryOp::Sub => (left.checked_sub(right), "subtract"),
+        };
+        match result {
+          Some(result) => Value::Int(result),
+          None => {
+            return Err(Diagnostic::error(
+              None,
+              &format!("attempt to {} with overflow", verb),
+              expr.span,
+              "the program panicked here",
+            ))
+          }
+        }
+      }
+      ExprKind::Call { name, args } => {
+        let values = args
+          .iter()
+          .map(|arg| self.expr(arg))
+          .collect::<Result<Vec<_>, _>>()?;
+        if name.name == "print" {
+          let line: Vec<String> = values.iter().map(|value| self.show(value)).collect();
+          writeln!(self.out, "{}", line.join(" ")).map_err(|error| {
+            Diagnostic::error(None, &format!("cannot print: {}", error), expr.span, "")
+          })?;
+        }
+        // `drop` has nothing to do, the checker took care of the move
+        Value::Unit
+      }
+      ExprKind::Method {
+        receiver,
+        method,
+        args,
+      } => {
+        let argument = match args.first() {
+          Some(arg) => Some(self.expr(arg)?),
+          None => None,
+        };
+        let slot = self.target(self.slot(receiver));
+        match (method.name.as_str(), argument) {
+          ("len", _) => match &self.slots[slot] {
+            Value::Str(text) => Value::Int(text.len() as i64),
+            _ => unreachable!("the checker only allows `len` on strings"),
+          },
+          ("clone", _) => self.slots[slot].clone(),
+          ("push_str", Some(argument)) => {
+            let tail = self.show(&argument);
+            if let Value::Str(text) = &mut self.slots[slot] {
+              text.push_str(&tail);
+            }
+            Value::Unit
+          }
+          _ => unreachable!("the checker only allows known methods"),
+        }
+      }
+    };
+    Ok(value)
+  }
+
+  fn int(&mut self, expr: &Expr) -> Result<i64, Diagnostic> {
+    match self.expr(expr)? {
+      Value::Int(value) => Ok(value),
+      _ => unreachable!("the checker only allows integer arithmetic"),
+    }
+  }
+
+  // How `print` shows a value, references print what they point at
+  fn show(&self, value: &Value) -> String {
+    match value {
+      Value::Int(value) => value.to_string(),
+      Value::Str(text) => text.clone(),
+      Value::Ref(slot) => self.show(&self.slots[*slot]),
+      Value::Unit => String::from("()"),
+    }
+  }
+}
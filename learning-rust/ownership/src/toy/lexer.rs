@@ -0,0 +1,141 @@
+// Source text to tokens, every token knows where it came from.
+
+use super::diagnostic::{Diagnostic, Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+  Let,
+  Mut,
+  Ident(String),
+  Int(i64),
+  Str(String),
+  Amp,
+  Star,
+  Eq,
+  Plus,
+  Minus,
+  Dot,
+  Comma,
+  Semi,
+  LParen,
+  RParen,
+  LBrace,
+  RBrace,
+  Eof,
+}
+
+impl Token {
+  // How the token is called in "expected `;`, found ..." messages
+  pub fn describe(&self) -> String {
+    match self {
+      Token::Let => String::from("`let`"),
+      Token::Mut => String::from("`mut`"),
+      Token::Ident(name) => format!("`{}`", name),
+      Token::Int(value) => format!("`{}`", value),
+      Token::Str(_) => String::from("a string"),
+      Token::Amp => String::from("`&`"),
+      Token::Star => String::from("`*`"),
+      Token::Eq => String::from("`=`"),
+      Token::Plus => String::from("`+`"),
+      Token::Minus => String::from("`-`"),
+      Token::Dot => String::from("`.`"),
+      Token::Comma => String::from("`,`"),
+      Token::Semi => String::from("`;`"),
+      Token::LParen => String::from("`(`"),
+      Token::RParen => String::from("`)`"),
+      Token::LBrace => String::from("`{`"),
+      Token::RBrace => String::from("`}`"),
+      Token::Eof => String::from("end of input"),
+    }
+  }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<(Token, Span)>, Diagnostic> {
+  let mut tokens = Vec::new();
+  let mut chars = source.char_indices().peekable();
+
+  while let Some((start, c)) = chars.next() {
+    let single = |token| (token, Span::new(start, start + c.len_utf8()));
+    let token = match c {
+      c if c.is_whitespace() => continue,
+      '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+        // a comment runs to the end of the line
+        while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+        continue;
+      }
+      '&' => single(Token::Amp),
+      '*' => single(Token::Star),
+      '=' => single(Token::Eq),
+      '+' => single(Token::Plus),
+      '-' => single(Token::Minus),
+      '.' => single(Token::Dot),
+      ',' => single(Token::Comma),
+      ';' => single(Token::Semi),
+      '(' => single(Token::LParen),
+      ')' => single(Token::RParen),
+      '{' => single(Token::LBrace),
+      '}' => single(Token::RBrace),
+      '"' => {
+        let mut text = String::new();
+        let end = loop {
+          match chars.next() {
+            Some((i, '"')) => break i + 1,
+            Some((_, c)) => text.push(c),
+            None => {
+              return Err(Diagnostic::error(
+                None,
+                "unterminated double quote string",
+                Span::new(start, source.len()),
+                "",
+              ))
+            }
+          }
+        };
+        (Token::Str(text), Span::new(start, end))
+      }
+      c if c.is_ascii_digit() => {
+        let mut end = start + 1;
+        while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '_') {
+          end = i + 1;
+        }
+        let span = Span::new(start, end);
+        let digits = source[start..end].replace('_', "");
+        match digits.parse() {
+          Ok(value) => (Token::Int(value), span),
+          Err(_) => {
+            return Err(Diagnostic::error(
+              None,
+              "integer literal is too large",
+              span,
+              "",
+            ))
+          }
+        }
+      }
+      c if c.is_alphabetic() || c == '_' => {
+        let mut end = start + c.len_utf8();
+        while let Some((i, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+          end = i + c.len_utf8();
+        }
+        let span = Span::new(start, end);
+        let token = match &source[start..end] {
+          "let" => Token::Let,
+          "mut" => Token::Mut,
+          word => Token::Ident(word.to_string()),
+        };
+        (token, span)
+      }
+      other => {
+        return Err(Diagnostic::error(
+          None,
+          &format!("unknown start of token: {}", other),
+          Span::new(start, start + other.len_utf8()),
+          "",
+        ))
+      }
+    };
+    tokens.push(token);
+  }
+  tokens.push((Token::Eof, Span::new(source.len(), source.len())));
+  Ok(tokens)
+}
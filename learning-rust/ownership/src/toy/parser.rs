@@ -0,0 +1,268 @@
+// Tokens to a syntax tree, a plain recursive descent parser.
+//
+//     program = stmt*
+//     stmt    = "let" "mut"? IDENT ("=" expr)? ";"
+//             | "*"? IDENT "=" expr ";"
+//             | expr ";"
+//             | "{" stmt* "}"
+//     expr    = unary (("+" | "-") unary)*
+//     unary   = "&" "mut"? IDENT | "*" IDENT | primary
+//     primary = INT | STRING | IDENT ("(" args ")" | "." IDENT "(" args ")")?
+//             | "(" expr ")"
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, Ident, Stmt};
+use super::diagnostic::{Diagnostic, Span};
+use super::lexer::Token;
+
+pub fn parse(tokens: Vec<(Token, Span)>) -> Result<Vec<Stmt>, Diagnostic> {
+  let mut parser = Parser {
+    tokens,
+    position: 0,
+  };
+  let mut stmts = Vec::new();
+  while parser.peek() != &Token::Eof {
+    stmts.push(parser.stmt()?);
+  }
+  Ok(stmts)
+}
+
+struct Parser {
+  tokens: Vec<(Token, Span)>,
+  position: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> &Token {
+    &self.tokens[self.position].0
+  }
+
+  fn peek_at(&self, ahead: usize) -> &Token {
+    let last = self.tokens.len() - 1;
+    &self.tokens[(self.position + ahead).min(last)].0
+  }
+
+  fn span(&self) -> Span {
+    self.tokens[self.position].1
+  }
+
+  fn advance(&mut self) -> (Token, Span) {
+    let token = self.tokens[self.position].clone();
+    if token.0 != Token::Eof {
+      self.position += 1;
+    }
+    token
+  }
+
+  fn eat(&mut self, token: &Token) -> bool {
+    if self.peek() == token {
+      self.advance();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, token: Token) -> Result<Span, Diagnostic> {
+    if self.peek() == &token {
+      Ok(self.advance().1)
+    } else {
+      Err(self.unexpected(&token.describe()))
+    }
+  }
+
+  fn unexpected(&self, expected: &str) -> Diagnostic {
+    let found = self.peek().describe();
+    Diagnostic::error(
+      None,
+      &format!("expected {}, found {}", expected, found),
+      self.span(),
+      &format!("expected {}", expected),
+    )
+  }
+
+  fn ident(&mut self) -> Result<Ident, Diagnostic> {
+    match self.peek().clone() {
+      Token::Ident(name) => {
+        let span = self.advance().1;
+        Ok(Ident { name, span })
+      }
+      _ => Err(self.unexpected("identifier")),
+    }
+  }
+
+  fn stmt(&mut self) -> Result<Stmt, Diagnostic> {
+    match self.peek() {
+      Token::Let => {
+        self.advance();
+        let mutable = self.eat(&Token::Mut);
+        let name = self.ident()?;
+        let init = if self.eat(&Token::Eq) {
+          Some(self.expr()?)
+        } else {
+          None
+        };
+        self.expect(Token::Semi)?;
+        Ok(Stmt::Let {
+          name,
+          mutable,
+          init,
+        })
+      }
+      Token::LBrace => Ok(Stmt::Block(self.block()?)),
+      // `*x = e;`
+      Token::Star if self.peek_at(2) == &Token::Eq => {
+        let start = self.advance().1;
+        let target = self.ident()?;
+        self.assignment(target, true, start)
+      }
+      // `x = e;`
+      Token::Ident(_) if self.peek_at(1) == &Token::Eq => {
+        let target = self.ident()?;
+        let start = target.span;
+        self.assignment(target, false, start)
+      }
+      _ => {
+        let expr = self.expr()?;
+        self.expect(Token::Semi)?;
+        Ok(Stmt::Expr(expr))
+      }
+    }
+  }
+
+  fn assignment(&mut self, target: Ident, deref: bool, start: Span) -> Result<Stmt, Diagnostic> {
+    self.expect(Token::Eq)?;
+    let value = self.expr()?;
+    let span = start.to(value.span);
+    self.expect(Token::Semi)?;
+    Ok(Stmt::Assign {
+      target,
+      deref,
+      value,
+      span,
+    })
+  }
+
+  fn block(&mut self) -> Result<Block, Diagnostic> {
+    self.expect(Token::LBrace)?;
+    let mut stmts = Vec::new();
+    while !matches!(self.peek(), Token::RBrace | Token::Eof) {
+      stmts.push(self.stmt()?);
+    }
+    let close = self.expect(Token::RBrace)?;
+    Ok(Block { stmts, close })
+  }
+
+  fn expr(&mut self) -> Result<Expr, Diagnostic> {
+    let mut left = self.unary()?;
+    loop {
+      let op = match self.peek() {
+        Token::Plus => BinaryOp::Add,
+        Token::Minus => BinaryOp::Sub,
+        _ => return Ok(left),
+      };
+      self.advance();
+      let right = self.unary()?;
+      let span = left.span.to(right.span);
+      left = Expr {
+        kind: ExprKind::Binary {
+          op,
+          left: Box::new(left),
+          right: Box::new(right),
+        },
+        span,
+      };
+    }
+  }
+
+  fn unary(&mut self) -> Result<Expr, Diagnostic> {
+    match self.peek() {
+      Token::Amp => {
+        let start = self.advance().1;
+        let mutable = self.eat(&Token::Mut);
+        let name = self.ident()?;
+        let span = start.to(name.span);
+        Ok(Expr {
+          kind: ExprKind::Borrow { mutable, name },
+          span,
+        })
+      }
+      Token::Star => {
+        let start = self.advance().1;
+        let name = self.ident()?;
+        let span = start.to(name.span);
+        Ok(Expr {
+          kind: ExprKind::Deref(name),
+          span,
+        })
+      }
+      _ => self.primary(),
+    }
+  }
+
+  fn primary(&mut self) -> Result<Expr, Diagnostic> {
+    let (token, span) = self.tokens[self.position].clone();
+    match token {
+      Token::Int(value) => {
+        self.advance();
+        Ok(Expr {
+          kind: ExprKind::Int(value),
+          span,
+        })
+      }
+      Token::Str(text) => {
+        self.advance();
+        Ok(Expr {
+          kind: ExprKind::Str(text),
+          span,
+        })
+      }
+      Token::LParen => {
+        self.advance();
+        let expr = self.expr()?;
+        self.expect(Token::RParen)?;
+        Ok(expr)
+      }
+      Token::Ident(_) => {
+        let name = self.ident()?;
+        if self.peek() == &Token::LParen {
+          let (args, end) = self.args()?;
+          Ok(Expr {
+            span: name.span.to(end),
+            kind: ExprKind::Call { name, args },
+          })
+        } else if self.eat(&Token::Dot) {
+          let method = self.ident()?;
+          let (args, end) = self.args()?;
+          Ok(Expr {
+            span: name.span.to(end),
+            kind: ExprKind::Method {
+              receiver: name,
+              method,
+              args,
+            },
+          })
+        } else {
+          Ok(Expr {
+            span: name.span,
+            kind: ExprKind::Var(name),
+          })
+        }
+      }
+      _ => Err(self.unexpected("expression")),
+    }
+  }
+
+  // `(a, b, c)`, returns the span of the closing parenthesis too
+  fn args(&mut self) -> Result<(Vec<Expr>, Span), Diagnostic> {
+    self.expect(Token::LParen)?;
+    let mut args = Vec::new();
+    while self.peek() != &Token::RParen {
+      args.push(self.expr()?);
+      if !self.eat(&Token::Comma) {
+        break;
+      }
+    }
+    let end = self.expect(Token::RParen)?;
+    Ok((args, end))
+  }
+}
@@ -0,0 +1,340 @@
+// An ownership timeline.
+// `Traced<T>` wraps a value and reports what happens to it: when it is
+// created, moved, cloned and dropped. `scope` marks blocks, so the timeline
+// shows exactly at which closing brace a value goes away.
+//
+//     let _scope = trace::scope("Move concept");
+//     let s1 = Traced::new("s1", String::from("hello"));
+//     let s2 = s1.move_to("s2");
+//
+// Rust does not tell a value that it was moved, a move is just a copy of the
+// bytes on the stack. So moves are spelled out with `move_to`, which consumes
+// the old binding (and the compiler will not let us use it again, exactly
+// like with a plain `let s2 = s1;`).
+//
+// Events are collected per thread. They are printed as they happen when
+// `set_echo(true)` was called, and `take_timeline` hands them out for JSON.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+  // a scope was entered or left
+  Enter,
+  Exit,
+  Create { value: String },
+  Move { from: String },
+  Clone { from: String },
+  Drop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+  // how many scopes were open when it happened
+  pub depth: usize,
+  // the scope, or the binding the event is about
+  pub name: String,
+  pub kind: EventKind,
+}
+
+impl fmt::Display for Event {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let indent = "  ".repeat(self.depth);
+    match &self.kind {
+      EventKind::Enter => write!(f, "{}{{ {}", indent, self.name),
+      EventKind::Exit => write!(f, "{}}} {}", indent, self.name),
+      EventKind::Create { value } => write!(f, "{}+ {} = {}", indent, self.name, value),
+      EventKind::Move { from } => write!(f, "{}> {} moved to {}", indent, from, self.name),
+      EventKind::Clone { from } => write!(f, "{}* {} cloned into {}", indent, from, self.name),
+      EventKind::Drop => write!(f, "{}- {} dropped", indent, self.name),
+    }
+  }
+}
+
+#[derive(Default)]
+struct Tracer {
+  depth: usize,
+  echo: bool,
+  events: Vec<Event>,
+}
+
+thread_local! {
+  static TRACER: RefCell<Tracer> = RefCell::new(Tracer::default());
+}
+
+fn record(name: &str, kind: EventKind) {
+  TRACER.with(|tracer| {
+    let mut tracer = tracer.borrow_mut();
+    if matches!(kind, EventKind::Exit) {
+      tracer.depth = tracer.depth.saturating_sub(1);
+    }
+    let event = Event {
+      depth: tracer.depth,
+      name: name.to_string(),
+      kind,
+    };
+    if matches!(event.kind, EventKind::Enter) {
+      tracer.depth += 1;
+    }
+    if tracer.echo {
+      println!("{}", event);
+    }
+    tracer.events.push(event);
+  });
+}
+
+// Print events as they happen (to stdout)
+pub fn set_echo(echo: bool) {
+  TRACER.with(|tracer| tracer.borrow_mut().echo = echo);
+}
+
+// All events recorded on this thread so far, the timeline starts over empty
+pub fn take_timeline() -> Vec<Event> {
+  TRACER.with(|tracer| std::mem::take(&mut tracer.borrow_mut().events))
+}
+
+// Marks a block until the returned guard is dropped. Create the guard first
+// in the block: locals are dropped in reverse order, so it goes last.
+pub fn scope(name: &str) -> Scope {
+  record(name, EventKind::Enter);
+  Scope {
+    name: name.to_string(),
+  }
+}
+
+pub struct Scope {
+  name: String,
+}
+
+impl Drop for Scope {
+  fn drop(&mut self) {
+    record(&self.name, EventKind::Exit);
+  }
+}
+
+pub struct Traced<T: fmt::Debug> {
+  name: String,
+  // `None` once the value moved on to another `Traced`, so its drop stays quiet
+  value: Option<T>,
+}
+
+impl<T: fmt::Debug> Traced<T> {
+  pub fn new(name: &str, value: T) -> Traced<T> {
+    record(
+      name,
+      EventKind::Create {
+        value: format!("{:?}", value),
+      },
+    );
+    Traced {
+      name: name.to_string(),
+      value: Some(value),
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  // `let to = self;`, recorded. Also use it for passing to and returning
+  // from functions, with a name like `takes_ownership(some_string)`.
+  pub fn move_to(mut self, to: &str) -> Traced<T> {
+    record(
+      to,
+      EventKind::Move {
+        from: self.name.clone(),
+      },
+    );
+    Traced {
+      name: to.to_string(),
+      value: self.value.take(),
+    }
+  }
+
+  // The value without the wrapper, nothing is recorded for it from now on
+  pub fn into_inner(mut self) -> T {
+    self
+      .value
+      .take()
+      .expect("a traced value is only emptied by moving it")
+  }
+}
+
+impl<T: fmt::Debug + Clone> Traced<T> {
+  // `let to = self.clone();`, recorded
+  pub fn clone_as(&self, to: &str) -> Traced<T> {
+    record(
+      to,
+      EventKind::Clone {
+        from: self.name.clone(),
+      },
+    );
+    Traced {
+      name: to.to_string(),
+      value: self.value.clone(),
+    }
+  }
+}
+
+impl<T: fmt::Debug + Clone> Clone for Traced<T> {
+  fn clone(&self) -> Traced<T> {
+    self.clone_as(&format!("{} (clone)", self.name))
+  }
+}
+
+impl<T: fmt::Debug> Drop for Traced<T> {
+  fn drop(&mut self) {
+    if self.value.is_some() {
+      record(&self.name, EventKind::Drop);
+    }
+  }
+}
+
+impl<T: fmt::Debug> Deref for Traced<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    self
+      .value
+      .as_ref()
+      .expect("a traced value is only emptied by moving it")
+  }
+}
+
+impl<T: fmt::Debug> DerefMut for Traced<T> {
+  fn deref_mut(&mut self) -> &mut T {
+    self
+      .value
+      .as_mut()
+      .expect("a traced value is only emptied by moving it")
+  }
+}
+
+impl<T: fmt::Debug + fmt::Display> fmt::Display for Traced<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(&**self, f)
+  }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Traced<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}: {:?}", self.name, self.value)
+  }
+}
+
+// The timeline as a JSON array, one object per event:
+//
+//     {"seq": 0, "depth": 1, "event": "move", "name": "s2", "from": "s1"}
+pub fn to_json(events: &[Event]) -> String {
+  let mut json = String::from("[\n");
+  for (seq, event) in events.iter().enumerate() {
+    let (kind, extra) = match &event.kind {
+      EventKind::Enter => ("enter", String::new()),
+      EventKind::Exit => ("exit", String::new()),
+      EventKind::Create { value } => ("create", format!(", \"value\": {}", quote(value))),
+      EventKind::Move { from } => ("move", format!(", \"from\": {}", quote(from))),
+      EventKind::Clone { from } => ("clone", format!(", \"from\": {}", quote(from))),
+      EventKind::Drop => ("drop", String::new()),
+    };
+    json.push_str(&format!(
+      "  {{\"seq\": {}, \"depth\": {}, \"event\": \"{}\", \"name\": {}{}}}",
+      seq,
+      event.depth,
+      kind,
+      quote(&event.name),
+      extra
+    ));
+    json.push_str(if seq + 1 < events.len() { ",\n" } else { "\n" });
+  }
+  json.push(']');
+  json
+}
+
+// A JSON string literal
+fn quote(text: &str) -> String {
+  let mut quoted = String::from("\"");
+  for c in text.chars() {
+    match c {
+      '"' => quoted.push_str("\\\""),
+      '\\' => quoted.push_str("\\\\"),
+      '\n' => quoted.push_str("\\n"),
+      '\r' => quoted.push_str("\\r"),
+      '\t' => quoted.push_str("\\t"),
+      c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+      c => quoted.push(c),
+    }
+  }
+  quoted.push('"');
+  quoted
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn timeline() -> Vec<String> {
+    take_timeline().iter().map(Event::to_string).collect()
+  }
+
+  #[test]
+  fn values_are_dropped_at_the_closing_brace_in_reverse_order() {
+    take_timeline();
+    {
+      let _scope = scope("outer");
+      let _a = Traced::new("a", 1);
+      {
+        let _scope = scope("inner");
+        let _b = Traced::new("b", 2);
+      }
+    }
+    assert_eq!(
+      timeline(),
+      [
+        "{ outer",
+        "  + a = 1",
+        "  { inner",
+        "    + b = 2",
+        "    - b dropped",
+        "  } inner",
+        "  - a dropped",
+        "} outer",
+      ]
+    );
+  }
+
+  #[test]
+  fn a_moved_value_is_dropped_once_under_its_new_name() {
+    take_timeline();
+    {
+      let s1 = Traced::new("s1", String::from("hi"));
+      let s2 = s1.move_to("s2");
+      let _s3 = s2.clone_as("s3");
+      assert_eq!(s2.len(), 2);
+    }
+    assert_eq!(
+      timeline(),
+      [
+        "+ s1 = \"hi\"",
+        "> s1 moved to s2",
+        "* s2 cloned into s3",
+        "- s3 dropped",
+        "- s2 dropped",
+      ]
+    );
+  }
+
+  #[test]
+  fn timelines_export_as_json() {
+    take_timeline();
+    drop(Traced::new("n", 7));
+    assert_eq!(
+      to_json(&take_timeline()),
+      "[\n  {\"seq\": 0, \"depth\": 0, \"event\": \"create\", \"name\": \"n\", \"value\": \"7\"},\n  \
+       {\"seq\": 1, \"depth\": 0, \"event\": \"drop\", \"name\": \"n\"}\n]"
+    );
+    assert_eq!(to_json(&[]), "[\n]");
+    assert_eq!(quote("say \"hi\"\n"), r#""say \"hi\"\n""#);
+  }
+}
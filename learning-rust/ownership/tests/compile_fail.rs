@@ -0,0 +1,95 @@
+// Programs the compiler must refuse.
+//
+// `tests/ui` holds the ones about the arena, mostly references that would
+// outlive the arena they point into. Each sits next to the error it is
+// expected to produce. After a compiler upgrade changes the wording:
+//
+//     TRYBUILD=overwrite cargo test --test compile_fail
+//
+// `tests/lessons` holds the lines the lessons leave commented out because
+// they do not compile, a file per lesson (and one more for a lesson that is
+// wrong in two ways). Those only say which errors they expect on which line,
+// as `//~ ERROR E0382` at the end of it (or `//~ ERROR E0596 E0502` for two),
+// so they keep working whatever the compiler's wording.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn arena_references_cannot_escape() {
+  let t = trybuild::TestCases::new();
+  t.compile_fail("tests/ui/arena_*.rs");
+}
+
+#[test]
+fn lesson_errors_are_still_errors() {
+  let mut paths: Vec<_> = fs::read_dir("tests/lessons")
+    .unwrap()
+    .map(|entry| entry.unwrap().path())
+    .collect();
+  paths.sort();
+  assert!(!paths.is_empty());
+
+  let mut failures = Vec::new();
+  for path in &paths {
+    let expected = expected_errors(&fs::read_to_string(path).unwrap());
+    assert!(!expected.is_empty(), "{} expects no error", path.display());
+    let actual = errors(path);
+    if actual != expected {
+      failures.push(format!(
+        "{}\n    expected {:?}\n    but got  {:?}",
+        path.display(),
+        expected,
+        actual
+      ));
+    }
+  }
+  assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+// (line, code) of every code in `//~ ERROR code...`
+fn expected_errors(source: &str) -> BTreeSet<(usize, String)> {
+  source
+    .lines()
+    .enumerate()
+    .filter_map(|(i, line)| Some((i + 1, line.split_once("//~ ERROR ")?.1)))
+    .flat_map(|(number, codes)| {
+      codes
+        .split_whitespace()
+        .map(move |code| (number, code.to_string()))
+    })
+    .collect()
+}
+
+// (line, code) of every error rustc reports for the file, in the short format:
+//
+//     tests/lessons/moves.rs:7:26: error[E0382]: borrow of moved value: `s1`
+fn errors(path: &Path) -> BTreeSet<(usize, String)> {
+  let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+  let output = Command::new(rustc)
+    .args([
+      "--edition",
+      "2021",
+      "--error-format",
+      "short",
+      "--emit",
+      "metadata",
+    ])
+    .arg("--out-dir")
+    .arg(env!("CARGO_TARGET_TMPDIR"))
+    .arg(path)
+    .output()
+    .unwrap();
+  String::from_utf8_lossy(&output.stderr)
+    .lines()
+    .filter_map(|line| {
+      let (location, rest) = line.split_once(": error[")?;
+      let (code, _) = rest.split_once(']')?;
+      let line_number = location.split(':').nth(1)?.parse().ok()?;
+      Some((line_number, code.to_string()))
+    })
+    .collect()
+}
@@ -0,0 +1,11 @@
+// "Dangling references": there is nothing the returned reference could borrow from.
+
+fn dangle() -> &String { //~ ERROR E0106
+  let s = String::from("hello");
+  &s
+}
+
+fn main() {
+  let reference_to_nothing = dangle();
+  println!("{}", reference_to_nothing);
+}
@@ -0,0 +1,10 @@
+// "Mixed references": no mutable reference while immutable ones are in use.
+// (The lesson's `s` is not `mut`, which would make this E0596 instead.)
+
+fn main() {
+  let mut s = String::from("hello");
+  let r1 = &s;
+  let r2 = &s;
+  let r3 = &mut s; //~ ERROR E0502
+  println!("{}, {}, {}", r1, r2, r3);
+}
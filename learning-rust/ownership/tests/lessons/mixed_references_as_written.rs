@@ -0,0 +1,11 @@
+// "Mixed references" exactly as the lesson has it, with `_r3` uncommented.
+// The lesson's `s` is not `mut`, so next to the mix of references (E0502)
+// the compiler also refuses to borrow `s` mutably at all (E0596).
+
+fn main() {
+  let s = String::from("Hello");
+  let _r1 = &s; // no problem
+  let _r2 = &s; // no problem
+  let _r3 = &mut s; // BIG PROBLEM //~ ERROR E0596 E0502
+  println!("{}, {}, {}", _r1, _r2, s);
+}
@@ -0,0 +1,9 @@
+// "Move concept": after `let s2 = s1;` only s2 owns the string.
+
+fn main() {
+  let s1 = String::from("hello");
+  let s2 = s1;
+
+  println!("{}, world!", s1); //~ ERROR E0382
+  println!("{}, world!", s2);
+}
@@ -0,0 +1,10 @@
+// "Multiple mutable references": only one at a time.
+
+fn main() {
+  let mut s = String::from("hello");
+
+  let r1 = &mut s;
+  let r2 = &mut s; //~ ERROR E0499
+
+  println!("{}, {}", r1, r2);
+}
@@ -0,0 +1,20 @@
+// "Ownership and Functions": a String passed by value is gone from the caller.
+
+fn takes_ownership(some_string: String) {
+  println!("{}", some_string);
+}
+
+fn makes_copy(some_integer: i32) {
+  println!("{}", some_integer);
+}
+
+fn main() {
+  let s = String::from("hello");
+  takes_ownership(s);
+  println!("{}", s); //~ ERROR E0382
+
+  // but an i32 is Copy, so x is still fine
+  let x = 5;
+  makes_copy(x);
+  println!("{}", x);
+}
@@ -0,0 +1,10 @@
+// "References": you cannot alter what is borrowed.
+
+fn change(some_string: &String) {
+  some_string.push_str(", world"); //~ ERROR E0596
+}
+
+fn main() {
+  let s = String::from("hello");
+  change(&s);
+}
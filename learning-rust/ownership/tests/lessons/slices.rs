@@ -0,0 +1,21 @@
+// "Slices": with `first_word` returning a `&str`, clearing the string while
+// the word is still in use no longer compiles.
+
+fn first_word(s: &String) -> &str {
+  let bytes = s.as_bytes();
+  for (i, &item) in bytes.iter().enumerate() {
+    if item == b' ' {
+      return &s[0..i];
+    }
+  }
+  &s[..]
+}
+
+fn main() {
+  let mut s = String::from("hello world");
+  let word = first_word(&s);
+
+  s.clear(); //~ ERROR E0502
+
+  println!("the first word is: {}", word);
+}
@@ -0,0 +1,71 @@
+// The pipeline's own count of allocations, checked against the allocator.
+// A test binary of its own because the counting allocator is global, and
+// with a single test nothing else allocates while the pipeline runs.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ownership::pipeline::Pipeline;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// Counts new blocks only, a `String` that grows is still one allocation
+unsafe impl GlobalAlloc for Counting {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout)
+  }
+
+  // the default `realloc` calls `alloc`, which would count every growth
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    System.realloc(ptr, layout, new_size)
+  }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+const TEXT: &str = "\
+The Rust Programming Language
+  ownership  is Rust's most unique feature
+and has deep implications for the rest of the language.
+
+it enables rust to make memory safety guarantees
+WITHOUT NEEDING A GARBAGE COLLECTOR,\tso it's important
+to understand how ownership works.
+";
+
+#[test]
+fn reported_allocations_are_real_ones() {
+  let mut pipeline = Pipeline::new()
+    .trim()
+    .lowercase()
+    .replace("rust", "Rust")
+    // longer than what it replaces, so the new `String` has to grow
+    .replace("o", "oo")
+    .collapse_whitespace();
+  let lines: Vec<&str> = TEXT.lines().collect();
+  let owned: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+
+  let before = ALLOCATIONS.load(Ordering::Relaxed);
+  let mut borrowed = 0;
+  for line in &lines {
+    if let std::borrow::Cow::Borrowed(_) = pipeline.run(*line) {
+      borrowed += 1;
+    }
+  }
+  for line in owned {
+    pipeline.run(line);
+  }
+  let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+  assert_eq!(allocations, pipeline.total().allocations);
+  assert_eq!(borrowed, pipeline.borrowed());
+  assert!(pipeline.total().avoided() > allocations);
+}
@@ -0,0 +1,11 @@
+// Dropping the arena frees everything in it, so it cannot be dropped while a
+// reference into it is still used.
+
+use ownership::arena::Arena;
+
+fn main() {
+  let arena = Arena::new();
+  let name = arena.alloc_str("ferris");
+  drop(arena);
+  println!("{}", name);
+}
@@ -0,0 +1,13 @@
+// The arena goes out of scope at the end of the block, the reference would
+// point at freed memory.
+
+use ownership::arena::Arena;
+
+fn main() {
+  let number;
+  {
+    let arena = Arena::new();
+    number = arena.alloc(5);
+  }
+  println!("{}", number);
+}
@@ -0,0 +1,12 @@
+// `fn dangle() -> &String` again: the arena is local to the function.
+
+use ownership::arena::Arena;
+
+fn dangle() -> &'static String {
+  let arena = Arena::new();
+  arena.alloc(String::from("hello"))
+}
+
+fn main() {
+  println!("{}", dangle());
+}
@@ -0,0 +1,12 @@
+// Allocating goes through a `RefCell`, two threads must not do it at once.
+
+use ownership::arena::Arena;
+use std::thread;
+
+fn main() {
+  let arena: Arena<u32> = Arena::new();
+  thread::scope(|scope| {
+    scope.spawn(|| arena.alloc(1));
+    arena.alloc(2);
+  });
+}
@@ -0,0 +1,55 @@
+// `wordstats` reading stdin, the way it is used at the end of a pipe.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn wordstats(args: &[&str], input: &str) -> String {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_wordstats"))
+    .args(args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .spawn()
+    .unwrap();
+  // dropped at the end of the statement, which closes stdin
+  child
+    .stdin
+    .take()
+    .unwrap()
+    .write_all(input.as_bytes())
+    .unwrap();
+  let output = child.wait_with_output().unwrap();
+  assert!(output.status.success());
+  String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn counts_what_comes_in_on_stdin() {
+  let out = wordstats(&["-n", "2"], "the crab\r\nand the crab's shell\nnaïve");
+  assert_eq!(
+    out,
+    "\
+lines                3
+words                7
+chars               36
+bytes               37
+distinct             6
+avg word          4.14
+
+2  the
+1  and
+"
+  );
+}
+
+#[test]
+fn a_dash_reads_stdin_too() {
+  let out = wordstats(&["-i", "-n", "1", "-"], "The THE the\n");
+  assert!(out.starts_with("lines                1\nwords                3\n"));
+  assert!(out.ends_with("\n3  the\n"));
+}
+
+#[test]
+fn a_long_line_without_a_newline() {
+  let out = wordstats(&["-n", "0"], &"crab ".repeat(100_000));
+  assert!(out.starts_with("lines                1\nwords           100000\n"));
+}
@@ -0,0 +1,58 @@
+// Shows an integer in every base, width and byte order.
+//
+//     bits 0xffff -128i8 "b'A'"
+//
+// Every argument is a Rust integer literal, see `variables::literal`. One
+// without a suffix can be as big as a `u128`.
+
+use std::env;
+use std::process;
+
+use variables::bits;
+use variables::literal;
+
+const USAGE: &str = "\
+Usage: bits <LITERAL>...
+
+Shows each integer LITERAL in decimal, hex, octal and binary, its bits and
+bytes in every width from 8 to 128 bits, and the types it fits in.
+
+A LITERAL is written like in Rust: 1_024, 0xffff, 0o77, 0b1111_0000, b'A',
+with a type suffix if you like (255u8), and with a `-` in front for a
+negative number (-128i8).
+
+Options:
+  -h, --help  print this help";
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  if args.is_empty() {
+    eprintln!("error: no literal given\n\n{}", USAGE);
+    process::exit(2);
+  }
+  if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+    println!("{}", USAGE);
+    return;
+  }
+
+  let mut failed = false;
+  for (i, arg) in args.iter().enumerate() {
+    if i > 0 {
+      println!();
+    }
+    match literal::parse_wide(arg) {
+      Ok(value) => print!("{}", bits::describe(value)),
+      Err(error) => {
+        // the literal again, with the part the error is about underlined
+        let width = arg[error.span.clone()].chars().count().max(1);
+        let column = arg[..error.span.start].chars().count();
+        eprintln!("error: {}", error);
+        eprintln!("  {}\n  {}{}", arg, " ".repeat(column), "^".repeat(width));
+        failed = true;
+      }
+    }
+  }
+  if failed {
+    process::exit(1);
+  }
+}
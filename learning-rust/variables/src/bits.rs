@@ -0,0 +1,189 @@
+// What an integer looks like inside the machine.
+// The "Scalar types" lesson stores `-128` in an `i8` and `255` in a `u8`,
+// and both are the eight bits `1000_0000` and `1111_1111`. `describe` shows
+// that for any value: the number in every base, the bits it has in each
+// width (negative numbers in two's complement), how those bits read as a
+// signed and as an unsigned number, its bytes in both byte orders and the
+// types it fits in.
+//
+// The bits of a value too big for a width are its lowest bits, which is what
+// an `as` cast to that width keeps.
+
+use std::fmt::Write;
+
+use crate::literal::{IntType, Value};
+
+pub const WIDTHS: [u32; 5] = [8, 16, 32, 64, 128];
+
+// The value as 128 bits, in two's complement
+pub fn to_bits(value: Value) -> u128 {
+  if value.is_negative() {
+    value.magnitude().wrapping_neg()
+  } else {
+    value.magnitude()
+  }
+}
+
+// The lowest `width` bits
+pub fn truncate(bits: u128, width: u32) -> u128 {
+  bits & (u128::MAX >> (128 - width))
+}
+
+// `width` bits read as a signed number: the top bit counts negative
+pub fn as_signed(bits: u128, width: u32) -> i128 {
+  let shift = 128 - width;
+  ((bits << shift) as i128) >> shift
+}
+
+// `1111_0000`, in groups of four from the right, always `width` digits
+pub fn binary(bits: u128, width: u32) -> String {
+  group(
+    &format!("{:0width$b}", truncate(bits, width), width = width as usize),
+    4,
+  )
+}
+
+// `00ff`, always `width / 4` digits
+pub fn hex(bits: u128, width: u32) -> String {
+  format!(
+    "{:0digits$x}",
+    truncate(bits, width),
+    digits = width as usize / 4
+  )
+}
+
+// The bytes from the lowest, as `to_le_bytes` gives them
+pub fn le_bytes(bits: u128, width: u32) -> Vec<u8> {
+  bits.to_le_bytes()[..width as usize / 8].to_vec()
+}
+
+fn group(digits: &str, size: usize) -> String {
+  let digits: Vec<char> = digits.chars().collect();
+  let groups: Vec<String> = digits
+    .rchunks(size)
+    .rev()
+    .map(|group| group.iter().collect())
+    .collect();
+  groups.join("_")
+}
+
+fn bytes(bytes: impl Iterator<Item = u8>) -> String {
+  bytes
+    .map(|byte| format!("{:02x}", byte))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+pub fn describe(value: Value) -> String {
+  let sign = if value.is_negative() { "-" } else { "" };
+  let magnitude = value.magnitude();
+  let bits = to_bits(value);
+  let mut out = String::new();
+
+  // writing to a `String` does not fail
+  let _ = writeln!(out, "{} ({})", value, value.ty());
+  let _ = writeln!(out, "  decimal  {}", value);
+  let _ = writeln!(out, "  hex      {}0x{:x}", sign, magnitude);
+  let _ = writeln!(out, "  octal    {}0o{:o}", sign, magnitude);
+  let _ = writeln!(
+    out,
+    "  binary   {}0b{}",
+    sign,
+    group(&format!("{:b}", magnitude), 4)
+  );
+
+  let (fits, does_not): (Vec<IntType>, Vec<IntType>) = IntType::ALL
+    .into_iter()
+    .partition(|ty| ty.fits(value.is_negative(), magnitude));
+  let names = |types: Vec<IntType>| {
+    let names: Vec<&str> = types.into_iter().map(IntType::name).collect();
+    if names.is_empty() {
+      String::from("-")
+    } else {
+      names.join(" ")
+    }
+  };
+  let _ = writeln!(out, "\nfits in          {}", names(fits));
+  let _ = writeln!(out, "does not fit in  {}", names(does_not));
+
+  let _ = writeln!(
+    out,
+    "\nwidth  {:<34}  {:>40}  {:>39}",
+    "hex", "as signed", "as unsigned"
+  );
+  for width in WIDTHS {
+    let cut = truncate(bits, width) != bits && as_signed(bits, width) as u128 != bits;
+    let _ = writeln!(
+      out,
+      "{:>5}  0x{:<32}  {:>40}  {:>39}{}",
+      width,
+      hex(bits, width),
+      as_signed(bits, width),
+      truncate(bits, width),
+      if cut { "  (cut off)" } else { "" }
+    );
+  }
+
+  let _ = writeln!(out, "\nwidth  bits");
+  for width in WIDTHS {
+    let _ = writeln!(out, "{:>5}  {}", width, binary(bits, width));
+  }
+
+  let _ = writeln!(out, "\nwidth  {:<47}  big endian", "little endian");
+  for width in WIDTHS {
+    let le = le_bytes(bits, width);
+    let _ = writeln!(
+      out,
+      "{:>5}  {:<47}  {}",
+      width,
+      bytes(le.iter().copied()),
+      bytes(le.iter().rev().copied())
+    );
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::literal::parse;
+
+  #[test]
+  fn the_lesson_values() {
+    // `let x: i8 = -128; let y: u8 = 255;`
+    let x = to_bits(Value::I8(-128));
+    let y = to_bits(Value::U8(255));
+    assert_eq!(binary(x, 8), "1000_0000");
+    assert_eq!(binary(y, 8), "1111_1111");
+    assert_eq!(as_signed(y, 8), -1);
+    assert_eq!(truncate(x, 8), 128);
+    assert_eq!(hex(x, 16), "ff80");
+    assert_eq!(binary(1, 16), "0000_0000_0000_0001");
+  }
+
+  #[test]
+  fn bytes_in_both_orders() {
+    let bits = to_bits(Value::U32(0x1234_5678));
+    assert_eq!(le_bytes(bits, 32), [0x78, 0x56, 0x34, 0x12]);
+    assert_eq!(le_bytes(bits, 16), [0x78, 0x56]);
+    assert_eq!(le_bytes(to_bits(Value::I16(-2)), 16), (-2i16).to_le_bytes());
+  }
+
+  #[test]
+  fn describes_every_part() {
+    let text = describe(parse("-128i8").unwrap());
+    assert!(text.starts_with("-128 (i8)\n"));
+    assert!(text.contains("  hex      -0x80\n"));
+    assert!(text.contains("fits in          i8 i16 i32 i64 i128 isize\n"));
+    assert!(text.contains("does not fit in  u8 u16 u32 u64 u128 usize\n"));
+    assert!(text.contains("\n    8  1000_0000\n"));
+    assert!(text.contains(&format!("\n    8  {:<47}  80\n", "80")));
+
+    // 300 does not fit in 8 bits, 0x2c is what is left
+    let text = describe(parse("300").unwrap());
+    assert!(text.contains(&format!(
+      "\n    8  0x{:<32}  {:>40}  {:>39}  (cut off)\n",
+      "2c", 44, 44
+    )));
+  }
+}
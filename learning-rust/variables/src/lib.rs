@@ -0,0 +1,5 @@
+// The variables lesson as a library, so `learn` can run its sections.
+
+pub mod bits;
+pub mod lessons;
+pub mod literal;
@@ -0,0 +1,657 @@
+// Integer literals, read the way Rust reads them.
+// The "Literals" lesson writes the same kind of number in every form Rust
+// has: `1_024`, `0xffff`, `0o77`, `0b1111_0000` and `b'A'`. `parse` takes
+// such a literal as a string and gives back a value of the right type:
+//
+//     assert_eq!(parse("0b1111_0000"), Ok(Value::I32(240)));
+//     assert_eq!(parse("255u8"), Ok(Value::U8(255)));
+//     assert_eq!(parse("b'A'"), Ok(Value::U8(65)));
+//
+// Like in Rust, a literal without a suffix is an `i32` and `_` may go
+// anywhere after the prefix. Unlike in Rust, a `-` in front is part of the
+// literal, so that `-128i8` fits (Rust negates a `128i8` that does not).
+// `parse_as` is for places where the type is known up front, like a field of
+// a config file, and `parse_wide` for tools that take any number at all.
+//
+// Every error says where in the string it is, as a byte range.
+
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntType {
+  I8,
+  I16,
+  I32,
+  I64,
+  I128,
+  Isize,
+  U8,
+  U16,
+  U32,
+  U64,
+  U128,
+  Usize,
+}
+
+impl IntType {
+  pub const ALL: [IntType; 12] = [
+    IntType::I8,
+    IntType::I16,
+    IntType::I32,
+    IntType::I64,
+    IntType::I128,
+    IntType::Isize,
+    IntType::U8,
+    IntType::U16,
+    IntType::U32,
+    IntType::U64,
+    IntType::U128,
+    IntType::Usize,
+  ];
+
+  // The suffix, which is also the name of the type
+  pub fn name(self) -> &'static str {
+    match self {
+      IntType::I8 => "i8",
+      IntType::I16 => "i16",
+      IntType::I32 => "i32",
+      IntType::I64 => "i64",
+      IntType::I128 => "i128",
+      IntType::Isize => "isize",
+      IntType::U8 => "u8",
+      IntType::U16 => "u16",
+      IntType::U32 => "u32",
+      IntType::U64 => "u64",
+      IntType::U128 => "u128",
+      IntType::Usize => "usize",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<IntType> {
+    IntType::ALL.into_iter().find(|ty| ty.name() == name)
+  }
+
+  // `isize` and `usize` are as wide as a pointer on the machine we run on
+  pub fn bits(self) -> u32 {
+    match self {
+      IntType::I8 | IntType::U8 => 8,
+      IntType::I16 | IntType::U16 => 16,
+      IntType::I32 | IntType::U32 => 32,
+      IntType::I64 | IntType::U64 => 64,
+      IntType::I128 | IntType::U128 => 128,
+      IntType::Isize | IntType::Usize => usize::BITS,
+    }
+  }
+
+  pub fn is_signed(self) -> bool {
+    matches!(
+      self,
+      IntType::I8 | IntType::I16 | IntType::I32 | IntType::I64 | IntType::I128 | IntType::Isize
+    )
+  }
+
+  // The biggest value, and the size of the smallest one (`128` for `i8`)
+  pub fn max(self) -> u128 {
+    u128::MAX >> (128 - self.bits() + self.is_signed() as u32)
+  }
+
+  pub fn min_magnitude(self) -> u128 {
+    if self.is_signed() {
+      self.max() + 1
+    } else {
+      0
+    }
+  }
+
+  // Whether `-magnitude` (or `magnitude`) is a value of this type
+  pub fn fits(self, negative: bool, magnitude: u128) -> bool {
+    if negative {
+      magnitude <= self.min_magnitude()
+    } else {
+      magnitude <= self.max()
+    }
+  }
+}
+
+impl fmt::Display for IntType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+  I8(i8),
+  I16(i16),
+  I32(i32),
+  I64(i64),
+  I128(i128),
+  Isize(isize),
+  U8(u8),
+  U16(u16),
+  U32(u32),
+  U64(u64),
+  U128(u128),
+  Usize(usize),
+}
+
+impl Value {
+  // `-magnitude` or `magnitude` as a `ty`, `None` when it does not fit
+  pub fn new(ty: IntType, negative: bool, magnitude: u128) -> Option<Value> {
+    if !ty.fits(negative, magnitude) {
+      return None;
+    }
+    // the two's complement, cut to the width of the type by the casts below
+    let bits = if negative {
+      magnitude.wrapping_neg()
+    } else {
+      magnitude
+    };
+    Some(match ty {
+      IntType::I8 => Value::I8(bits as i8),
+      IntType::I16 => Value::I16(bits as i16),
+      IntType::I32 => Value::I32(bits as i32),
+      IntType::I64 => Value::I64(bits as i64),
+      IntType::I128 => Value::I128(bits as i128),
+      IntType::Isize => Value::Isize(bits as isize),
+      IntType::U8 => Value::U8(bits as u8),
+      IntType::U16 => Value::U16(bits as u16),
+      IntType::U32 => Value::U32(bits as u32),
+      IntType::U64 => Value::U64(bits as u64),
+      IntType::U128 => Value::U128(bits),
+      IntType::Usize => Value::Usize(bits as usize),
+    })
+  }
+
+  pub fn ty(self) -> IntType {
+    match self {
+      Value::I8(_) => IntType::I8,
+      Value::I16(_) => IntType::I16,
+      Value::I32(_) => IntType::I32,
+      Value::I64(_) => IntType::I64,
+      Value::I128(_) => IntType::I128,
+      Value::Isize(_) => IntType::Isize,
+      Value::U8(_) => IntType::U8,
+      Value::U16(_) => IntType::U16,
+      Value::U32(_) => IntType::U32,
+      Value::U64(_) => IntType::U64,
+      Value::U128(_) => IntType::U128,
+      Value::Usize(_) => IntType::Usize,
+    }
+  }
+
+  // The value in a wider type, `i128` does not have room for every `u128`
+  pub fn to_i128(self) -> Option<i128> {
+    match self {
+      Value::I8(n) => Some(n.into()),
+      Value::I16(n) => Some(n.into()),
+      Value::I32(n) => Some(n.into()),
+      Value::I64(n) => Some(n.into()),
+      Value::I128(n) => Some(n),
+      Value::Isize(n) => Some(n as i128),
+      Value::U8(n) => Some(n.into()),
+      Value::U16(n) => Some(n.into()),
+      Value::U32(n) => Some(n.into()),
+      Value::U64(n) => Some(n.into()),
+      Value::U128(n) => i128::try_from(n).ok(),
+      Value::Usize(n) => Some(n as i128),
+    }
+  }
+
+  pub fn is_negative(self) -> bool {
+    self.to_i128().is_some_and(|n| n < 0)
+  }
+
+  // The value without its sign
+  pub fn magnitude(self) -> u128 {
+    match self {
+      Value::U128(n) => n,
+      other => other.to_i128().unwrap().unsigned_abs(),
+    }
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Value::U128(n) => write!(f, "{}", n),
+      other => write!(f, "{}", other.to_i128().unwrap()),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+  pub kind: ErrorKind,
+  // the bytes of the input the error is about
+  pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+  Empty,
+  // no digits where they should start, like in `0x`, `0b_` or `_1`
+  NoDigits,
+  InvalidDigit { digit: char, radix: u32 },
+  // `1.5`, `1e3`
+  NotAnInteger,
+  UnknownSuffix(String),
+  // `256u8`, the value as it was written
+  Overflow { ty: IntType },
+  // `-1u8`
+  NegativeUnsigned { ty: IntType },
+  // the literal has a suffix and `parse_as` wanted another type
+  WrongType { expected: IntType, found: IntType },
+  // something wrong inside `b'...'`
+  InvalidByte(String),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match &self.kind {
+      ErrorKind::Empty => write!(f, "no number"),
+      ErrorKind::NoDigits => write!(f, "no digits"),
+      ErrorKind::InvalidDigit { digit, radix } => {
+        write!(f, "invalid digit `{}` for a base {} literal", digit, radix)
+      }
+      ErrorKind::NotAnInteger => write!(f, "not an integer"),
+      ErrorKind::UnknownSuffix(suffix) => write!(f, "invalid suffix `{}`", suffix),
+      ErrorKind::Overflow { ty } => write!(f, "literal out of range for `{}`", ty),
+      ErrorKind::NegativeUnsigned { ty } => write!(f, "`{}` cannot be negative", ty),
+      ErrorKind::WrongType { expected, found } => {
+        write!(f, "expected a `{}`, the suffix says `{}`", expected, found)
+      }
+      ErrorKind::InvalidByte(message) => write!(f, "invalid byte literal: {}", message),
+    }?;
+    write!(f, " at {}", self.span.start)
+  }
+}
+
+impl std::error::Error for Error {}
+
+// Any integer literal, its type is the suffix or `i32`
+pub fn parse(text: &str) -> Result<Value, Error> {
+  parse_literal(text, Want::Default)
+}
+
+// A literal of type `ty`. It does not need a suffix, but a suffix has to say `ty`.
+pub fn parse_as(text: &str, ty: IntType) -> Result<Value, Error> {
+  parse_literal(text, Want::Exactly(ty))
+}
+
+// Any number at all: without a suffix it is an `i128`, or a `u128` when it is
+// too big for that
+pub fn parse_wide(text: &str) -> Result<Value, Error> {
+  parse_literal(text, Want::Widest)
+}
+
+// The type of a literal without a suffix
+#[derive(Clone, Copy)]
+enum Want {
+  Default,
+  Exactly(IntType),
+  Widest,
+}
+
+fn error(kind: ErrorKind, span: Range<usize>) -> Error {
+  Error { kind, span }
+}
+
+fn parse_literal(text: &str, want: Want) -> Result<Value, Error> {
+  if text.is_empty() {
+    return Err(error(ErrorKind::Empty, 0..0));
+  }
+  let negative = text.starts_with('-');
+  let start = negative as usize;
+  let body = &text[start..];
+  if body.is_empty() {
+    return Err(error(ErrorKind::Empty, start..start));
+  }
+
+  if body.starts_with("b'") {
+    let (byte, end) = byte_literal(text, start)?;
+    if end < text.len() {
+      // Rust has no suffixes on byte literals, `b'A'` is always a `u8`
+      let suffix = &text[end..];
+      return Err(error(
+        ErrorKind::UnknownSuffix(suffix.to_string()),
+        end..text.len(),
+      ));
+    }
+    let ty = match want {
+      Want::Exactly(ty) => ty,
+      Want::Default | Want::Widest => IntType::U8,
+    };
+    return finish(text, negative, byte.into(), ty, start..end);
+  }
+
+  let (magnitude, suffix_start) = digits(text, start)?;
+
+  let suffix = &text[suffix_start..];
+  let ty = if suffix.is_empty() {
+    match want {
+      Want::Default => IntType::I32,
+      Want::Exactly(ty) => ty,
+      Want::Widest if negative || magnitude.is_some_and(|n| n <= IntType::I128.max()) => {
+        IntType::I128
+      }
+      Want::Widest => IntType::U128,
+    }
+  } else {
+    let found = IntType::from_name(suffix).ok_or_else(|| {
+      error(
+        ErrorKind::UnknownSuffix(suffix.to_string()),
+        suffix_start..text.len(),
+      )
+    })?;
+    match want {
+      Want::Exactly(expected) if expected != found => {
+        return Err(error(
+          ErrorKind::WrongType { expected, found },
+          suffix_start..text.len(),
+        ));
+      }
+      _ => found,
+    }
+  };
+  // `None` did not even fit a `u128`
+  let magnitude =
+    magnitude.ok_or_else(|| error(ErrorKind::Overflow { ty }, start..suffix_start))?;
+  finish(text, negative, magnitude, ty, start..suffix_start)
+}
+
+fn finish(
+  text: &str,
+  negative: bool,
+  magnitude: u128,
+  ty: IntType,
+  number: Range<usize>,
+) -> Result<Value, Error> {
+  if negative && !ty.is_signed() && magnitude != 0 {
+    return Err(error(ErrorKind::NegativeUnsigned { ty }, 0..text.len()));
+  }
+  Value::new(ty, negative, magnitude).ok_or_else(|| {
+    // `-129i8` is out of range as a whole, the `-` included
+    let span = if negative { 0..number.end } else { number };
+    error(ErrorKind::Overflow { ty }, span)
+  })
+}
+
+// The number at `start`: its value (`None` if it does not fit a `u128`) and
+// where the suffix starts
+fn digits(text: &str, start: usize) -> Result<(Option<u128>, usize), Error> {
+  let body = &text[start..];
+  let (radix, prefix) = match body.get(..2) {
+    Some("0x") => (16, 2),
+    Some("0o") => (8, 2),
+    Some("0b") => (2, 2),
+    _ => (10, 0),
+  };
+  if radix == 10 && body.starts_with('_') {
+    return Err(error(ErrorKind::NoDigits, start..start + 1));
+  }
+
+  let mut value = Some(0u128);
+  let mut any = false;
+  let mut end = text.len();
+  for (i, c) in body[prefix..].char_indices() {
+    let at = start + prefix + i;
+    if c == '_' {
+      continue;
+    }
+    match c.to_digit(radix) {
+      Some(digit) => {
+        value = value
+          .and_then(|value| value.checked_mul(radix.into()))
+          .and_then(|value| value.checked_add(digit.into()));
+        any = true;
+      }
+      // `0b2` and `0o8` have a digit that is wrong, not a suffix
+      None if c.is_ascii_digit() => {
+        return Err(error(
+          ErrorKind::InvalidDigit { digit: c, radix },
+          at..at + 1,
+        ));
+      }
+      None => {
+        end = at;
+        break;
+      }
+    }
+  }
+  if !any {
+    return Err(error(ErrorKind::NoDigits, start..end));
+  }
+
+  let rest = &text[end..];
+  let exponent = rest.starts_with(['e', 'E'])
+    && rest[1..]
+      .trim_start_matches(['+', '-', '_'])
+      .starts_with(|c: char| c.is_ascii_digit());
+  if radix == 10 && (rest.starts_with('.') || exponent) {
+    return Err(error(ErrorKind::NotAnInteger, start..text.len()));
+  }
+  // a letter after the digits: `0x1g` or `0b1a` are bad suffixes in Rust too
+  Ok((value, end))
+}
+
+// `b'A'`, `b'\n'` or `b'\x7f'` at `start`: the byte and where the literal ends
+fn byte_literal(text: &str, start: usize) -> Result<(u8, usize), Error> {
+  let open = start + 2;
+  let invalid =
+    |message: &str, span: Range<usize>| error(ErrorKind::InvalidByte(message.to_string()), span);
+  let inside = &text[open..];
+  let (byte, length) = match inside.chars().next() {
+    None => return Err(invalid("missing the closing `'`", start..text.len())),
+    Some('\'') => return Err(invalid("empty", start..open + 1)),
+    Some('\\') => {
+      escape(&inside[1..]).ok_or_else(|| invalid("unknown escape", open..text.len()))?
+    }
+    Some(c) if c.is_ascii() && c != '\n' && c != '\t' => (c as u8, 1),
+    Some(c) => {
+      return Err(invalid(
+        "only ASCII characters, other bytes need `\\x`",
+        open..open + c.len_utf8(),
+      ))
+    }
+  };
+  let close = open + length;
+  if !text[close..].starts_with('\'') {
+    return Err(invalid("missing the closing `'`", start..text.len()));
+  }
+  Ok((byte, close + 1))
+}
+
+// The escape after a `\`: the byte and how long it was, the `\` included
+fn escape(after: &str) -> Option<(u8, usize)> {
+  let byte = match after.chars().next()? {
+    'n' => b'\n',
+    'r' => b'\r',
+    't' => b'\t',
+    '\\' => b'\\',
+    '0' => b'\0',
+    '\'' => b'\'',
+    '"' => b'"',
+    'x' => {
+      let hex = after.get(1..3)?;
+      if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+      }
+      return Some((u8::from_str_radix(hex, 16).ok()?, 4));
+    }
+    _ => return None,
+  };
+  Some((byte, 2))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn kind(text: &str) -> ErrorKind {
+    parse(text).unwrap_err().kind
+  }
+
+  fn span(text: &str) -> Range<usize> {
+    parse(text).unwrap_err().span
+  }
+
+  #[test]
+  fn every_form_of_the_lesson() {
+    assert_eq!(parse("1_024i16"), Ok(Value::I16(1_024)));
+    assert_eq!(parse("0xffffu16"), Ok(Value::U16(0xffff)));
+    assert_eq!(parse("0o77u32"), Ok(Value::U32(0o77)));
+    assert_eq!(parse("0b1111_0000"), Ok(Value::I32(0b1111_0000)));
+    assert_eq!(parse("b'A'"), Ok(Value::U8(b'A')));
+    assert_eq!(parse("-128i8"), Ok(Value::I8(-128)));
+    assert_eq!(parse("255u8"), Ok(Value::U8(255)));
+    assert_eq!(parse("0x_ff_u8"), Ok(Value::U8(255)));
+    assert_eq!(parse("1_"), Ok(Value::I32(1)));
+    assert_eq!(
+      parse("340282366920938463463374607431768211455u128"),
+      Ok(Value::U128(u128::MAX))
+    );
+    assert_eq!(
+      parse("-0x8000_0000_0000_0000_0000_0000_0000_0000i128"),
+      Ok(Value::I128(i128::MIN))
+    );
+    assert_eq!(parse("-0u8"), Ok(Value::U8(0)));
+  }
+
+  #[test]
+  fn byte_escapes() {
+    assert_eq!(parse(r"b'\n'"), Ok(Value::U8(b'\n')));
+    assert_eq!(parse(r"b'\''"), Ok(Value::U8(b'\'')));
+    assert_eq!(parse(r"b'\x7f'"), Ok(Value::U8(0x7f)));
+    assert_eq!(parse_as("b'A'", IntType::U32), Ok(Value::U32(65)));
+    assert!(matches!(kind(r"b'\q'"), ErrorKind::InvalidByte(_)));
+    assert!(matches!(kind("b'é'"), ErrorKind::InvalidByte(_)));
+    assert!(matches!(kind("b'AB'"), ErrorKind::InvalidByte(_)));
+    assert!(matches!(kind("b''"), ErrorKind::InvalidByte(_)));
+    assert_eq!(kind("b'A'u8"), ErrorKind::UnknownSuffix(String::from("u8")));
+  }
+
+  #[test]
+  fn overflow_is_for_the_target_type() {
+    assert_eq!(kind("256u8"), ErrorKind::Overflow { ty: IntType::U8 });
+    assert_eq!(span("256u8"), 0..3);
+    assert_eq!(kind("128i8"), ErrorKind::Overflow { ty: IntType::I8 });
+    assert_eq!(kind("-129i8"), ErrorKind::Overflow { ty: IntType::I8 });
+    assert_eq!(span("-129i8"), 0..4);
+    assert_eq!(kind("0xffi8"), ErrorKind::Overflow { ty: IntType::I8 });
+    assert_eq!(kind("2147483648"), ErrorKind::Overflow { ty: IntType::I32 });
+    assert_eq!(
+      kind("340282366920938463463374607431768211456u128"),
+      ErrorKind::Overflow { ty: IntType::U128 }
+    );
+    assert_eq!(
+      kind("-1u8"),
+      ErrorKind::NegativeUnsigned { ty: IntType::U8 }
+    );
+  }
+
+  #[test]
+  fn malformed_digits_have_positions() {
+    assert_eq!(
+      kind("0b1021"),
+      ErrorKind::InvalidDigit {
+        digit: '2',
+        radix: 2
+      }
+    );
+    assert_eq!(span("0b1021"), 4..5);
+    assert_eq!(
+      kind("0o78"),
+      ErrorKind::InvalidDigit {
+        digit: '8',
+        radix: 8
+      }
+    );
+    assert_eq!(span("0o78"), 3..4);
+    assert_eq!(kind("0x"), ErrorKind::NoDigits);
+    assert_eq!(kind("0b__"), ErrorKind::NoDigits);
+    assert_eq!(kind("_1"), ErrorKind::NoDigits);
+    assert_eq!(kind("1.5"), ErrorKind::NotAnInteger);
+    assert_eq!(kind("1e3"), ErrorKind::NotAnInteger);
+    assert_eq!(kind("12abc"), ErrorKind::UnknownSuffix(String::from("abc")));
+    assert_eq!(span("12abc"), 2..5);
+    assert_eq!(kind("0x1g"), ErrorKind::UnknownSuffix(String::from("g")));
+    assert_eq!(kind(""), ErrorKind::Empty);
+    assert_eq!(kind("-"), ErrorKind::Empty);
+    assert_eq!(
+      parse("0b1021").unwrap_err().to_string(),
+      "invalid digit `2` for a base 2 literal at 4"
+    );
+  }
+
+  #[test]
+  fn parse_as_a_known_type() {
+    assert_eq!(parse_as("200", IntType::U8), Ok(Value::U8(200)));
+    assert_eq!(parse_as("200u8", IntType::U8), Ok(Value::U8(200)));
+    assert_eq!(
+      parse_as("200u16", IntType::U8).unwrap_err().kind,
+      ErrorKind::WrongType {
+        expected: IntType::U8,
+        found: IntType::U16
+      }
+    );
+    assert_eq!(
+      parse_as("300", IntType::U8).unwrap_err().kind,
+      ErrorKind::Overflow { ty: IntType::U8 }
+    );
+  }
+
+  #[test]
+  fn the_limits_of_every_type() {
+    for ty in IntType::ALL {
+      let max = parse(&format!("{}{}", ty.max(), ty)).unwrap();
+      assert_eq!((max.ty(), max.magnitude()), (ty, ty.max()));
+      if let Some(over) = ty.max().checked_add(1) {
+        assert_eq!(
+          parse(&format!("{}{}", over, ty)).unwrap_err().kind,
+          ErrorKind::Overflow { ty }
+        );
+      }
+      if ty.is_signed() {
+        let min = parse(&format!("-{}{}", ty.min_magnitude(), ty)).unwrap();
+        assert!(min.is_negative());
+        assert_eq!(min.magnitude(), ty.min_magnitude());
+      }
+    }
+  }
+
+  #[test]
+  fn wide_numbers() {
+    assert_eq!(parse_wide("300"), Ok(Value::I128(300)));
+    assert_eq!(parse_wide("-300"), Ok(Value::I128(-300)));
+    assert_eq!(
+      parse_wide("0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff"),
+      Ok(Value::U128(u128::MAX))
+    );
+    assert_eq!(parse_wide("b'A'"), Ok(Value::U8(65)));
+    assert_eq!(
+      parse_wide("300u8").unwrap_err().kind,
+      ErrorKind::Overflow { ty: IntType::U8 }
+    );
+    assert_eq!(
+      parse_wide("-0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff")
+        .unwrap_err()
+        .kind,
+      ErrorKind::Overflow { ty: IntType::I128 }
+    );
+  }
+
+  #[test]
+  fn values_know_their_type() {
+    let value = parse("-5i64").unwrap();
+    assert_eq!(value.ty(), IntType::I64);
+    assert!(value.is_negative());
+    assert_eq!(value.magnitude(), 5);
+    assert_eq!(value.to_string(), "-5");
+    assert_eq!(Value::U128(u128::MAX).to_i128(), None);
+    assert_eq!(IntType::I8.max(), 127);
+    assert_eq!(IntType::I8.min_magnitude(), 128);
+    assert_eq!(IntType::U128.max(), u128::MAX);
+  }
+}